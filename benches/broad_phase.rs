@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use criterion::{
+  black_box,
+  criterion_group,
+  criterion_main,
+  BenchmarkId,
+  Criterion,
+};
+use floppy_burp::collision::{
+  sweep_and_prune,
+  Collider,
+  CollisionLayers,
+  Shape,
+};
+
+/// Pipes spread along x as in a run, with a flock of birds among the first
+/// ones.
+fn bodies(pipes: usize, birds: usize) -> Vec<(CollisionLayers, Collider)> {
+  let pipe = Shape::Rectangle(Rectangle::new(52.0, 320.0));
+  let bird = Shape::Circle(Circle::new(12.0));
+  let obstacle_layers =
+    CollisionLayers::new(CollisionLayers::OBSTACLE, CollisionLayers::BIRD);
+  let bird_layers =
+    CollisionLayers::new(CollisionLayers::BIRD, CollisionLayers::OBSTACLE);
+  let pipes = (0..pipes).map(|i| {
+    let y = if i % 2 == 0 { 220.0 } else { -220.0 };
+    let position = Vec2::new((i / 2) as f32 * 150.0, y + (i % 7) as f32 * 10.0);
+    (obstacle_layers, pipe.to_collider(position, Quat::IDENTITY))
+  });
+  let birds = (0..birds).map(|i| {
+    let position = Vec2::new((i % 10) as f32 * 30.0, (i % 13) as f32 * 20.0);
+    (bird_layers, bird.to_collider(position, Quat::IDENTITY))
+  });
+  pipes.chain(birds).collect()
+}
+
+fn brute_force(bodies: &[(CollisionLayers, Collider)]) -> usize {
+  let mut contacts = 0;
+  for (i, (layers_a, a)) in bodies.iter().enumerate() {
+    for (layers_b, b) in &bodies[i + 1..] {
+      if layers_a.interacts(*layers_b) && a.contact(b).is_some() {
+        contacts += 1;
+      }
+    }
+  }
+  contacts
+}
+
+fn swept(bodies: &[(CollisionLayers, Collider)]) -> usize {
+  let aabbs: Vec<_> =
+    bodies.iter().map(|(_, collider)| collider.aabb()).collect();
+  sweep_and_prune(&aabbs)
+    .into_iter()
+    .filter(|&(i, j)| {
+      let ((layers_a, a), (layers_b, b)) = (&bodies[i], &bodies[j]);
+      layers_a.interacts(*layers_b) && a.contact(b).is_some()
+    })
+    .count()
+}
+
+fn contacts(c: &mut Criterion) {
+  let mut group = c.benchmark_group("contacts");
+  for (pipes, birds) in [(10, 1), (200, 8), (1000, 64)] {
+    let bodies = bodies(pipes, birds);
+    assert_eq!(brute_force(&bodies), swept(&bodies));
+    let id = format!("{pipes} pipes, {birds} birds");
+    group.bench_with_input(
+      BenchmarkId::new("brute_force", &id),
+      &bodies,
+      |b, bodies| b.iter(|| brute_force(black_box(bodies))),
+    );
+    group.bench_with_input(
+      BenchmarkId::new("sweep_and_prune", &id),
+      &bodies,
+      |b, bodies| b.iter(|| swept(black_box(bodies))),
+    );
+  }
+  group.finish();
+}
+
+criterion_group!(benches, contacts);
+criterion_main!(benches);
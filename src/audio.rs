@@ -0,0 +1,280 @@
+use std::{sync::Arc, time::Duration};
+
+use bevy::{
+  audio::{AddAudioSource, Source, Volume},
+  prelude::*,
+};
+
+use crate::{
+  bird::{BirdDied, Flapped, HitObstacle, ObstacleKind},
+  checkpoint::CheckpointPassed,
+  settings::Settings,
+  state::GameState,
+};
+
+/// Plays sound effects and music. The game ships no audio files, so all
+/// sounds are synthesized.
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_audio_source::<Synth>()
+      .add_systems(Startup, (load_sounds, start_music).chain())
+      .add_systems(
+        Update,
+        (
+          play_sound_effects.run_if(resource_exists::<Sounds>),
+          set_music_volume.run_if(resource_changed::<Settings>),
+        ),
+      )
+      .add_systems(OnEnter(GameState::Paused), pause_audio)
+      .add_systems(OnExit(GameState::Paused), resume_audio);
+  }
+}
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Time it takes a note to reach full volume, so it doesn't start with a click.
+const ATTACK_SECS: f32 = 0.005;
+
+/// A sound made of notes played one after another.
+#[derive(Asset, TypePath, Clone)]
+pub struct Synth {
+  notes: Arc<[Note]>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Note {
+  wave: Wave,
+  /// Pitch at the start of the note, sliding linearly to `end_hz`.
+  start_hz: f32,
+  end_hz: f32,
+  secs: f32,
+  volume: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Wave {
+  Square,
+  Triangle,
+  Noise,
+}
+
+impl Note {
+  const fn new(wave: Wave, start_hz: f32, end_hz: f32, secs: f32) -> Self {
+    Self {
+      wave,
+      start_hz,
+      end_hz,
+      secs,
+      volume: 0.25,
+    }
+  }
+
+  /// A note of the given MIDI pitch.
+  fn midi(wave: Wave, pitch: u8, secs: f32) -> Self {
+    let hz = 440.0 * 2f32.powf((pitch as f32 - 69.0) / 12.0);
+    Self::new(wave, hz, hz, secs)
+  }
+
+  const fn with_volume(mut self, volume: f32) -> Self {
+    self.volume = volume;
+    self
+  }
+}
+
+impl Synth {
+  fn new(notes: impl IntoIterator<Item = Note>) -> Self {
+    Self {
+      notes: notes.into_iter().collect(),
+    }
+  }
+}
+
+impl Decodable for Synth {
+  type DecoderItem = f32;
+  type Decoder = SynthDecoder;
+
+  fn decoder(&self) -> Self::Decoder {
+    SynthDecoder {
+      notes: self.notes.clone(),
+      note: 0,
+      sample: 0,
+      phase: 0.0,
+      noise: 0x9e37_79b9,
+    }
+  }
+}
+
+pub struct SynthDecoder {
+  notes: Arc<[Note]>,
+  note: usize,
+  /// Index of the next sample within the current note.
+  sample: u32,
+  /// Position within the period of the wave, from 0 to 1.
+  phase: f32,
+  /// State of the xorshift generator of noise.
+  noise: u32,
+}
+
+impl Iterator for SynthDecoder {
+  type Item = f32;
+
+  fn next(&mut self) -> Option<f32> {
+    loop {
+      let note = self.notes.get(self.note)?;
+      let samples = (note.secs * SAMPLE_RATE as f32) as u32;
+      if self.sample >= samples {
+        self.note += 1;
+        self.sample = 0;
+        continue;
+      }
+      let progress = self.sample as f32 / samples as f32;
+      let secs = self.sample as f32 / SAMPLE_RATE as f32;
+      let hz = note.start_hz.lerp(note.end_hz, progress);
+      self.phase = (self.phase + hz / SAMPLE_RATE as f32).fract();
+      self.sample += 1;
+      let value = match note.wave {
+        Wave::Square if self.phase < 0.5 => 1.0,
+        Wave::Square => -1.0,
+        Wave::Triangle => 4.0 * (self.phase - 0.5).abs() - 1.0,
+        Wave::Noise => {
+          self.noise ^= self.noise << 13;
+          self.noise ^= self.noise >> 17;
+          self.noise ^= self.noise << 5;
+          self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0
+        }
+      };
+      let envelope = (secs / ATTACK_SECS).min(1.0) * (1.0 - progress);
+      return Some(value * envelope * note.volume);
+    }
+  }
+}
+
+impl Source for SynthDecoder {
+  fn current_frame_len(&self) -> Option<usize> {
+    None
+  }
+
+  fn channels(&self) -> u16 {
+    1
+  }
+
+  fn sample_rate(&self) -> u32 {
+    SAMPLE_RATE
+  }
+
+  fn total_duration(&self) -> Option<Duration> {
+    let secs = self.notes.iter().map(|note| note.secs).sum();
+    Some(Duration::from_secs_f32(secs))
+  }
+}
+
+/// Handles to the sound effects.
+#[derive(Resource)]
+struct Sounds {
+  flap: Handle<Synth>,
+  score: Handle<Synth>,
+  hit: Handle<Synth>,
+  die: Handle<Synth>,
+}
+
+/// Marks the entity playing the music.
+#[derive(Component)]
+struct Music;
+
+fn load_sounds(mut commands: Commands, mut synths: ResMut<Assets<Synth>>) {
+  use Wave::*;
+  commands.insert_resource(Sounds {
+    flap: synths.add(Synth::new([Note::new(Square, 220.0, 440.0, 0.07)])),
+    score: synths.add(Synth::new([
+      Note::new(Triangle, 988.0, 988.0, 0.06).with_volume(0.4),
+      Note::new(Triangle, 1319.0, 1319.0, 0.2).with_volume(0.4),
+    ])),
+    hit: synths.add(Synth::new([
+      Note::new(Noise, 0.0, 0.0, 0.15).with_volume(0.4)
+    ])),
+    die: synths.add(Synth::new([Note::new(Square, 440.0, 110.0, 0.4)])),
+  });
+}
+
+fn start_music(
+  mut commands: Commands,
+  settings: Res<Settings>,
+  mut synths: ResMut<Assets<Synth>>,
+) {
+  // an arpeggio over C, Am, F and G
+  const PITCHES: [u8; 16] = [
+    60, 64, 67, 72, 57, 60, 64, 69, 53, 57, 60, 65, 55, 59, 62, 67,
+  ];
+  let tune = Synth::new(
+    PITCHES
+      .map(|pitch| Note::midi(Wave::Triangle, pitch, 0.2).with_volume(0.15)),
+  );
+  commands.spawn((
+    Music,
+    AudioPlayer(synths.add(tune)),
+    PlaybackSettings::LOOP.with_volume(music_volume(&settings)),
+  ));
+}
+
+fn play_sound_effects(
+  mut commands: Commands,
+  settings: Res<Settings>,
+  sounds: Res<Sounds>,
+  mut flapped_events: EventReader<Flapped>,
+  mut checkpoint_events: EventReader<CheckpointPassed>,
+  mut hit_events: EventReader<HitObstacle>,
+  mut died_events: EventReader<BirdDied>,
+) {
+  let mut play = |sound: &Handle<Synth>| {
+    commands.spawn((
+      AudioPlayer(sound.clone()),
+      PlaybackSettings::DESPAWN.with_volume(Volume::new(
+        settings.master_volume * settings.effects_volume,
+      )),
+    ));
+  };
+  // several events in one frame would only make the sound louder
+  if flapped_events.read().count() > 0 {
+    play(&sounds.flap);
+  }
+  if checkpoint_events.read().count() > 0 {
+    play(&sounds.score);
+  }
+  if hit_events.read().count() > 0 {
+    play(&sounds.hit);
+  }
+  // the bird only falls after hitting a pipe
+  if died_events
+    .read()
+    .any(|died| died.cause == ObstacleKind::Pipe)
+  {
+    play(&sounds.die);
+  }
+}
+
+fn music_volume(settings: &Settings) -> Volume {
+  Volume::new(settings.master_volume * settings.music_volume)
+}
+
+fn set_music_volume(
+  settings: Res<Settings>,
+  sinks: Query<&AudioSink, With<Music>>,
+) {
+  for sink in &sinks {
+    sink.set_volume(music_volume(&settings).get());
+  }
+}
+
+fn pause_audio(sinks: Query<&AudioSink>) {
+  for sink in &sinks {
+    sink.pause();
+  }
+}
+
+fn resume_audio(sinks: Query<&AudioSink>) {
+  for sink in &sinks {
+    sink.play();
+  }
+}
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use crate::{
+  bird::{
+    Bird,
+    BirdController,
+    BirdInputSet,
+    DefaultBirdController,
+    FlapInput,
+    Velocity,
+  },
+  config::GameConfig,
+  input::{update_actions, InputAction},
+  physics::Position,
+  pipe::{next_gap, Pipe, PipeGap},
+  replay::Playback,
+  state::GameState,
+};
+
+/// Flies birds controlled by [`BirdController::Autopilot`], and runs demos
+/// with them on the title screen.
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_systems(
+        FixedUpdate,
+        fly_autopilot.in_set(BirdInputSet).run_if(
+          in_state(GameState::Going).and(not(resource_exists::<Playback>)),
+        ),
+      )
+      .add_systems(
+        PreUpdate,
+        end_demo
+          .after(update_actions)
+          .run_if(resource_exists::<Demo>),
+      )
+      .add_systems(
+        OnEnter(GameState::MainMenu),
+        finish_demo.run_if(resource_exists::<Demo>),
+      );
+  }
+}
+
+/// Exists while the autopilot plays a run to show off the game. Demo runs
+/// don't make it to the leaderboard and aren't saved as replays.
+#[derive(Resource, Debug)]
+pub struct Demo {
+  /// Controller to give birds back once the demo is over.
+  player_controller: BirdController,
+  game_over_secs: f32,
+}
+
+impl Demo {
+  /// How long the game-over screen of a demo stays up.
+  const GAME_OVER_SECS: f32 = 3.0;
+}
+
+/// How high above the bottom of a gap the autopilot keeps the bird. A flap
+/// lifts the bird by about a third of the gap.
+const MARGIN: f32 = 10.0;
+
+fn fly_autopilot(
+  time: Res<Time>,
+  config: Res<GameConfig>,
+  pipes: Query<(&Position, &PipeGap), With<Pipe>>,
+  mut birds: Query<
+    (&BirdController, &Position, &Velocity, &mut FlapInput),
+    With<Bird>,
+  >,
+) {
+  let radius = config.bird.hitbox_radius;
+  for (controller, position, velocity, mut flap_input) in &mut birds {
+    if *controller != BirdController::Autopilot {
+      continue;
+    }
+    let lowest_y = next_gap(&pipes, position.x, radius)
+      .map_or(0.0, |(_, gap)| {
+        gap.center - gap.height / 2.0 + radius + MARGIN
+      });
+    let next_y = position.y + **velocity * time.delta_secs();
+    // the button is released between flaps so that each press counts
+    flap_input.0 = !flap_input.0 && next_y < lowest_y;
+  }
+}
+
+/// Starts a demo run, flown by the autopilot.
+pub(crate) fn start_demo(
+  mut commands: Commands,
+  mut default_controller: ResMut<DefaultBirdController>,
+  mut next_state: ResMut<NextState<GameState>>,
+) {
+  commands.insert_resource(Demo {
+    player_controller: default_controller.0,
+    game_over_secs: 0.0,
+  });
+  default_controller.0 = BirdController::Autopilot;
+  next_state.set(GameState::Going);
+}
+
+/// Goes back to the title screen on any input, or a while after the demo
+/// bird dies.
+fn end_demo(
+  time: Res<Time>,
+  state: Res<State<GameState>>,
+  mut demo: ResMut<Demo>,
+  mut actions: ResMut<ButtonInput<InputAction>>,
+  mut next_state: ResMut<NextState<GameState>>,
+) {
+  if *state.get() == GameState::GameOver {
+    demo.game_over_secs += time.delta_secs();
+  }
+  if actions.get_just_pressed().next().is_some() {
+    // the input only ends the demo
+    actions.reset_all();
+    next_state.set(GameState::MainMenu);
+  } else if demo.game_over_secs >= Demo::GAME_OVER_SECS {
+    next_state.set(GameState::MainMenu);
+  }
+}
+
+fn finish_demo(
+  mut commands: Commands,
+  demo: Res<Demo>,
+  mut default_controller: ResMut<DefaultBirdController>,
+) {
+  default_controller.0 = demo.player_controller;
+  commands.remove_resource::<Demo>();
+}
@@ -1,6 +1,12 @@
 use bevy::{prelude::*, sprite::Anchor};
 
-use crate::{layer::Layer, state::GameState, GAME_SPEED, RESOLUTION};
+use crate::{
+  difficulty::Difficulty,
+  layer::Layer,
+  settings::Themed,
+  state::GameState,
+  RESOLUTION,
+};
 
 pub struct BackgroundPlugin;
 
@@ -10,8 +16,11 @@ impl Plugin for BackgroundPlugin {
       .add_systems(Startup, init_background)
       .add_systems(
         Update,
-        animate_background
-          .run_if(in_state(GameState::Idle).or(in_state(GameState::Going))),
+        animate_background.run_if(
+          in_state(GameState::MainMenu)
+            .or(in_state(GameState::Idle))
+            .or(in_state(GameState::Going)),
+        ),
       );
   }
 }
@@ -20,12 +29,14 @@ impl Plugin for BackgroundPlugin {
 struct Background;
 
 impl Background {
-  pub const SCROLL_SPEED: f32 = GAME_SPEED / 3.0;
+  /// Scroll speed relative to the game speed.
+  pub const PARALLAX: f32 = 1.0 / 3.0;
 }
 
 fn init_background(mut commands: Commands, asset_server: Res<AssetServer>) {
   commands.spawn((
     Background,
+    Themed,
     Sprite {
       image: asset_server.load("background-day.png"),
       anchor: Anchor::CenterLeft,
@@ -38,9 +49,11 @@ fn init_background(mut commands: Commands, asset_server: Res<AssetServer>) {
 
 fn animate_background(
   time: Res<Time>,
+  difficulty: Res<Difficulty>,
   mut transform: Single<&mut Transform, With<Background>>,
 ) {
-  transform.translation.x -= time.delta_secs() * Background::SCROLL_SPEED;
+  transform.translation.x -=
+    time.delta_secs() * difficulty.game_speed * Background::PARALLAX;
   if transform.translation.x <= RESOLUTION.x / 2.0 - 276.0 {
     transform.translation.x = -RESOLUTION.x / 2.0;
   }
@@ -1,13 +1,18 @@
 use std::ops::Add;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashSet};
 
 use crate::{
-  collision::Shape,
+  collision::{CollisionLayers, CollisionStarted, Shape},
+  config::GameConfig,
   ground::Ground,
+  input::{update_actions, InputAction},
   layer::Layer,
+  physics::{PhysicsSet, Position},
   pipe::Pipe,
-  state::GameState,
+  replay::Playback,
+  score::BirdScore,
+  state::{GameState, OnRunStart},
   RESOLUTION,
 };
 
@@ -16,25 +21,54 @@ pub struct BirdPlugin;
 impl Plugin for BirdPlugin {
   fn build(&self, app: &mut App) {
     app
+      .add_event::<Flapped>()
+      .add_event::<HitObstacle>()
+      .add_event::<BirdDied>()
       .add_systems(OnEnter(GameState::Idle), respawn_bird)
+      .add_systems(OnEnter(GameState::MainMenu), despawn_bird)
+      .add_systems(OnRunStart, respawn_bird)
       .add_systems(
-        OnTransition {
-          exited: GameState::GameOver,
-          entered: GameState::Going,
-        },
-        respawn_bird,
+        Update,
+        animate_bird
+          .run_if(in_state(GameState::Idle).or(in_state(GameState::Going))),
       )
+      .init_resource::<BirdCount>()
+      .init_resource::<DefaultBirdController>()
+      .init_resource::<BirdControllers>()
+      .init_resource::<FlapLatch>()
       .add_systems(
-        Update,
+        PreUpdate,
+        latch_flap_presses
+          .after(update_actions)
+          .run_if(in_state(GameState::Going)),
+      )
+      .add_systems(OnRunStart, clear_flap_latch)
+      .add_systems(OnExit(GameState::Paused), ignore_held_flaps)
+      .configure_sets(FixedUpdate, BirdInputSet.before(PhysicsSet::Movement))
+      .add_systems(
+        FixedUpdate,
         (
-          (update_bird, detect_collisions).run_if(in_state(GameState::Going)),
-          animate_bird
-            .run_if(in_state(GameState::Idle).or(in_state(GameState::Going))),
-        ),
+          read_flap_action
+            .in_set(BirdInputSet)
+            .run_if(not(resource_exists::<Playback>)),
+          control_bird
+            .after(BirdInputSet)
+            .before(PhysicsSet::Movement),
+          tilt_bird.after(PhysicsSet::Movement),
+          hit_obstacles.after(PhysicsSet::Collision),
+        )
+          .run_if(in_state(GameState::Going)),
       )
       .add_systems(
         FixedUpdate,
-        control_bird.run_if(in_state(GameState::Going)),
+        (
+          update_bird.in_set(PhysicsSet::Movement),
+          tumble_bird.after(PhysicsSet::Movement),
+          land_bird
+            .after(PhysicsSet::Movement)
+            .run_if(in_state(GameState::Dying)),
+        )
+          .run_if(in_state(GameState::Going).or(in_state(GameState::Dying))),
       );
   }
 }
@@ -42,15 +76,151 @@ impl Plugin for BirdPlugin {
 #[derive(Component)]
 pub struct Bird;
 
+/// Who a bird belongs to. Every bird belongs to the first player, unless
+/// two players play against each other.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum Player {
+  #[default]
+  One,
+  Two,
+}
+
+impl Player {
+  pub const ALL: [Self; 2] = [Self::One, Self::Two];
+
+  /// Action that flaps the birds of the player.
+  pub fn flap_action(self) -> InputAction {
+    match self {
+      Self::One => InputAction::Flap,
+      Self::Two => InputAction::Flap2,
+    }
+  }
+
+  /// Color the bird sprite is tinted with, turning the second player's bird
+  /// red.
+  pub fn tint(self) -> Color {
+    match self {
+      Self::One => Color::WHITE,
+      Self::Two => Color::srgb(1.0, 0.45, 0.45),
+    }
+  }
+
+  /// Color the bird appears in once tinted, for whatever labels it.
+  pub fn color(self) -> Color {
+    // the bird sprite is mostly this yellow
+    let yellow = LinearRgba::from(Srgba::rgb(1.0, 0.84, 0.0));
+    let tint = self.tint().to_linear();
+    LinearRgba::rgb(
+      yellow.red * tint.red,
+      yellow.green * tint.green,
+      yellow.blue * tint.blue,
+    )
+    .into()
+  }
+}
+
+/// Marks a bird that has hit something. It falls to the ground and takes no
+/// further part in the run.
+#[derive(Component, Debug)]
+pub struct Dead;
+
+/// How many birds a run starts with. They fly the same course independently,
+/// and the run goes on until the last one dies.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct BirdCount(pub usize);
+
+impl Default for BirdCount {
+  fn default() -> Self {
+    Self(1)
+  }
+}
+
 #[derive(Component, Deref, Clone, Copy, Default, Debug)]
 pub struct Velocity(f32);
 
+/// Whether the flap button is held on the current fixed tick. The bird
+/// flaps when it gets pressed.
+#[derive(Component, Deref, DerefMut, Clone, Copy, Default, Debug)]
+pub struct FlapInput(pub bool);
+
+/// Decides what sets the [`FlapInput`] of a bird.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum BirdController {
+  /// The [`Player`] the bird belongs to, through their flap action.
+  #[default]
+  Player,
+  /// The built-in [autopilot](crate::autopilot).
+  Autopilot,
+  /// Systems outside of the game, which should run in [`BirdInputSet`].
+  External,
+}
+
+/// Controller that birds get when they spawn.
+#[derive(Resource, Clone, Copy, Default, Debug)]
+pub struct DefaultBirdController(pub BirdController);
+
+/// Controllers of the birds a run starts with, in the order they spawn in.
+/// Birds past the end of the list get the [`DefaultBirdController`].
+#[derive(Resource, Clone, Default, Debug)]
+pub struct BirdControllers(pub Vec<BirdController>);
+
+/// What the bird remembers between ticks to turn [`FlapInput`] into flaps.
+#[derive(Component, Clone, Copy, Default, Debug)]
+pub struct FlapState {
+  was_held: bool,
+  cooldown_secs: f32,
+  /// Time left to flap after a press made during the cooldown.
+  buffered_secs: Option<f32>,
+  /// How long the button has been held since the last flap, if it has.
+  hold_secs: Option<f32>,
+}
+
+/// Players whose flap action was pressed since the last fixed tick, so that
+/// taps shorter than a tick aren't lost.
+#[derive(Resource, Default, Debug)]
+struct FlapLatch(HashSet<Player>);
+
+/// Sent when a bird flaps.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct Flapped {
+  pub bird: Entity,
+}
+
+/// Sent when a bird runs into something.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct HitObstacle {
+  pub bird: Entity,
+  pub kind: ObstacleKind,
+}
+
+/// Sent when a hit kills a bird. The game is over once the last one dies.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct BirdDied {
+  pub bird: Entity,
+  pub cause: ObstacleKind,
+  /// Score of the bird.
+  pub score: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ObstacleKind {
+  Pipe,
+  Ground,
+}
+
+/// Systems that set the [`FlapInput`] of the bird.
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BirdInputSet;
+
 impl Bird {
-  pub const FLAP_FORCE: f32 = 300.0;
-  pub const GRAVITY_COEF: f32 = 1800.0;
-  pub const VEL_TO_ANGLE_RATIO: f32 = 8.0;
-  pub const HITBOX_SIZE: f32 = 10.0;
   pub const SPRITE_SIZE: UVec2 = UVec2 { x: 34, y: 24 };
+  /// How fast the bird spins while falling after a hit, in degrees per second.
+  const TUMBLE_SPEED: f32 = 540.0;
+
+  /// Lowest height the bird can be at, resting on the ground.
+  fn floor(config: &GameConfig) -> f32 {
+    -RESOLUTION.y / 2.0 + config.bird.hitbox_radius + config.ground.level
+  }
 }
 
 #[derive(Component)]
@@ -59,92 +229,265 @@ struct AnimationConfig {
   timer: Timer,
 }
 
-fn respawn_bird(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn respawn_bird(
   mut commands: Commands,
-  asset_server: Res<AssetServer>,
-  mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+  config: Res<GameConfig>,
+  default_controller: Res<DefaultBirdController>,
+  controllers: Res<BirdControllers>,
+  asset_server: Option<Res<AssetServer>>,
+  texture_atlas_layouts: Option<ResMut<Assets<TextureAtlasLayout>>>,
+  bird_count: Res<BirdCount>,
   query: Query<Entity, With<Bird>>,
 ) {
-  if let Ok(entity) = query.get_single() {
+  for entity in &query {
     commands.entity(entity).despawn();
   }
-  commands.spawn((
-    Bird,
-    Sprite::from_atlas_image(
-      asset_server.load("yellowbird-sheet.png"),
-      TextureAtlas {
-        layout: texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
-          Bird::SPRITE_SIZE,
-          4,
-          1,
-          None,
-          None,
-        )),
-        index: 0,
-      },
-    ),
-    Transform::from_xyz(-RESOLUTION.x / 4.0, 0.0, Layer::Bird.into()),
-    Shape::Circle(Circle::new(Bird::HITBOX_SIZE)),
-    Velocity::default(),
-    AnimationConfig {
-      total_frames: 4,
-      timer: Timer::from_seconds(0.1, TimerMode::Repeating),
+  let spawn_point = Vec2::new(-RESOLUTION.x / 4.0, 0.0);
+  // the atlas is shared by all birds
+  let sprite = asset_server.zip(texture_atlas_layouts).map(
+    |(asset_server, mut texture_atlas_layouts)| {
+      Sprite::from_atlas_image(
+        asset_server.load("yellowbird-sheet.png"),
+        TextureAtlas {
+          layout: texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
+            Bird::SPRITE_SIZE,
+            4,
+            1,
+            None,
+            None,
+          )),
+          index: 0,
+        },
+      )
     },
-  ));
+  );
+  for index in 0..bird_count.0 {
+    let controller = controllers.0.get(index).copied();
+    let mut bird = commands.spawn((
+      Bird,
+      Position::interpolated(spawn_point),
+      Transform::from_translation(spawn_point.extend(Layer::Bird.into())),
+      Shape::Circle(Circle::new(config.bird.hitbox_radius)),
+      CollisionLayers::new(CollisionLayers::BIRD, CollisionLayers::OBSTACLE),
+      Velocity::default(),
+      FlapInput::default(),
+      FlapState::default(),
+      controller.unwrap_or(default_controller.0),
+      Player::default(),
+      BirdScore::default(),
+    ));
+    if let Some(sprite) = &sprite {
+      bird.insert((
+        sprite.clone(),
+        AnimationConfig {
+          total_frames: 4,
+          timer: Timer::from_seconds(0.1, TimerMode::Repeating),
+        },
+      ));
+    }
+  }
+}
+
+fn despawn_bird(mut commands: Commands, query: Query<Entity, With<Bird>>) {
+  for bird in &query {
+    commands.entity(bird).despawn();
+  }
 }
 
 fn update_bird(
   time: Res<Time>,
-  query: Single<(&mut Velocity, &mut Transform), With<Bird>>,
+  config: Res<GameConfig>,
+  mut query: Query<(&mut Velocity, &mut Position), With<Bird>>,
 ) {
-  let (mut velocity, mut transform) = query.into_inner();
-  velocity.0 -= time.delta_secs() * Bird::GRAVITY_COEF;
-  transform.translation.y = transform
-    .translation
-    .y
-    .add(velocity.0 * time.delta_secs())
-    .clamp(
-      -RESOLUTION.y / 2.0 + Bird::HITBOX_SIZE + Ground::LEVEL,
-      RESOLUTION.y / 2.0 + Bird::HITBOX_SIZE * 2.0,
+  for (mut velocity, mut position) in &mut query {
+    velocity.0 -= time.delta_secs() * config.bird.gravity;
+    position.y = position.y.add(velocity.0 * time.delta_secs()).clamp(
+      Bird::floor(&config),
+      RESOLUTION.y / 2.0 + config.bird.hitbox_radius * 2.0,
     );
-  transform.rotation = Quat::from_axis_angle(
-    Vec3::Z,
-    f32::clamp(velocity.0 / Bird::VEL_TO_ANGLE_RATIO, -90.0, 90.0).to_radians(),
-  );
+  }
+}
+
+fn tilt_bird(
+  config: Res<GameConfig>,
+  mut query: Query<(&Velocity, &mut Transform), (With<Bird>, Without<Dead>)>,
+) {
+  for (velocity, mut transform) in &mut query {
+    transform.rotation = Quat::from_axis_angle(
+      Vec3::Z,
+      f32::clamp(
+        velocity.0 / config.bird.velocity_to_angle_ratio,
+        -90.0,
+        90.0,
+      )
+      .to_radians(),
+    );
+  }
+}
+
+fn tumble_bird(
+  time: Res<Time>,
+  config: Res<GameConfig>,
+  mut query: Query<(&Position, &mut Transform), (With<Bird>, With<Dead>)>,
+) {
+  for (position, mut transform) in &mut query {
+    if position.y > Bird::floor(&config) {
+      transform
+        .rotate_z(-(Bird::TUMBLE_SPEED * time.delta_secs()).to_radians());
+    }
+  }
+}
+
+/// Ends the game once the last bird to die has fallen to the ground.
+fn land_bird(
+  config: Res<GameConfig>,
+  mut next_state: ResMut<NextState<GameState>>,
+  query: Query<&Position, With<Bird>>,
+) {
+  if query
+    .iter()
+    .all(|position| position.y <= Bird::floor(&config))
+  {
+    next_state.set(GameState::GameOver);
+  }
 }
 
 fn animate_bird(
   time: Res<Time>,
-  query: Single<(&mut Sprite, &mut AnimationConfig), With<Bird>>,
+  mut query: Query<
+    (&mut Sprite, &mut AnimationConfig),
+    (With<Bird>, Without<Dead>),
+  >,
 ) {
-  let (mut sprite, mut config) = query.into_inner();
-  config.timer.tick(time.delta());
-  if config.timer.just_finished() {
-    let atlas = &mut sprite.texture_atlas.as_mut().unwrap();
-    atlas.index = (atlas.index + 1) % config.total_frames;
+  for (mut sprite, mut config) in &mut query {
+    config.timer.tick(time.delta());
+    if config.timer.just_finished() {
+      let atlas = &mut sprite.texture_atlas.as_mut().unwrap();
+      atlas.index = (atlas.index + 1) % config.total_frames;
+    }
+  }
+}
+
+fn latch_flap_presses(
+  actions: Res<ButtonInput<InputAction>>,
+  mut latch: ResMut<FlapLatch>,
+) {
+  for player in Player::ALL {
+    if actions.just_pressed(player.flap_action()) {
+      latch.0.insert(player);
+    }
   }
 }
 
+fn clear_flap_latch(mut latch: ResMut<FlapLatch>) {
+  latch.0.clear();
+}
+
+/// Keeps flap keys held from the pause screen from flapping once the run
+/// resumes.
+fn ignore_held_flaps(mut query: Query<&mut FlapState, With<Bird>>) {
+  for mut state in &mut query {
+    state.was_held = true;
+    state.hold_secs = None;
+  }
+}
+
+fn read_flap_action(
+  actions: Res<ButtonInput<InputAction>>,
+  mut latch: ResMut<FlapLatch>,
+  mut query: Query<(&BirdController, &Player, &mut FlapInput), With<Bird>>,
+) {
+  for (controller, player, mut flap_input) in &mut query {
+    if *controller == BirdController::Player {
+      flap_input.0 =
+        actions.pressed(player.flap_action()) || latch.0.contains(player);
+    }
+  }
+  latch.0.clear();
+}
+
 fn control_bird(
-  keys: Res<ButtonInput<KeyCode>>,
-  mut velocity: Single<&mut Velocity, With<Bird>>,
+  time: Res<Time>,
+  config: Res<GameConfig>,
+  mut events: EventWriter<Flapped>,
+  mut query: Query<
+    (Entity, &FlapInput, &mut FlapState, &mut Velocity),
+    (With<Bird>, Without<Dead>),
+  >,
 ) {
-  if keys.pressed(KeyCode::Space) {
-    velocity.0 = Bird::FLAP_FORCE;
+  let delta_secs = time.delta_secs();
+  for (bird, flap_input, mut state, mut velocity) in &mut query {
+    let just_pressed = flap_input.0 && !state.was_held;
+    state.was_held = flap_input.0;
+    state.cooldown_secs = (state.cooldown_secs - delta_secs).max(0.0);
+    if just_pressed {
+      state.buffered_secs = Some(config.bird.flap_buffer_secs);
+    }
+    if !flap_input.0 {
+      state.hold_secs = None;
+    }
+    if let Some(buffered_secs) = state.buffered_secs {
+      if state.cooldown_secs <= 0.0 {
+        velocity.0 = config.bird.flap_force;
+        state.cooldown_secs = config.bird.flap_cooldown_secs;
+        state.buffered_secs = None;
+        state.hold_secs = flap_input.0.then_some(0.0);
+        events.send(Flapped { bird });
+        continue;
+      }
+      state.buffered_secs =
+        (buffered_secs > 0.0).then_some(buffered_secs - delta_secs);
+    }
+    if let Some(hold_secs) = &mut state.hold_secs {
+      *hold_secs += delta_secs;
+      if config.bird.hold_to_flap_higher
+        && *hold_secs <= config.bird.max_hold_secs
+      {
+        velocity.0 = config.bird.flap_force;
+      }
+    }
   }
 }
 
-fn detect_collisions(
-  mut next_state: ResMut<NextState<GameState>>,
-  bird_query: Single<(&Shape, &Transform), With<Bird>>,
-  obstacle_query: Query<(&Shape, &Transform), Or<(With<Pipe>, With<Ground>)>>,
+pub(crate) fn hit_obstacles(
+  mut commands: Commands,
+  mut collision_events: EventReader<CollisionStarted>,
+  mut hit_events: EventWriter<HitObstacle>,
+  mut died_events: EventWriter<BirdDied>,
+  mut birds: Query<(&mut Velocity, &BirdScore), (With<Bird>, Without<Dead>)>,
+  obstacles: Query<Has<Ground>, Or<(With<Pipe>, With<Ground>)>>,
 ) {
-  let bird_collider = bird_query.0.to_collider(bird_query.1.translation.xy());
-  for obstacle in &obstacle_query {
-    if bird_collider
-      .collides(&obstacle.0.to_collider(obstacle.1.translation.xy()))
-    {
-      next_state.set(GameState::GameOver);
+  let mut dead_birds = Vec::new();
+  for event in collision_events.read() {
+    let (bird, other) = if birds.contains(event.a) {
+      (event.a, event.b)
+    } else {
+      (event.b, event.a)
+    };
+    // a bird can hit several obstacles on the same tick
+    if dead_birds.contains(&bird) {
+      continue;
     }
+    let (Ok((mut velocity, score)), Ok(is_ground)) =
+      (birds.get_mut(bird), obstacles.get(other))
+    else {
+      continue;
+    };
+    let kind = if is_ground {
+      ObstacleKind::Ground
+    } else {
+      ObstacleKind::Pipe
+    };
+    // the bird stops rising and falls to the ground
+    velocity.0 = velocity.0.min(0.0);
+    commands.entity(bird).insert(Dead);
+    dead_birds.push(bird);
+    hit_events.send(HitObstacle { bird, kind });
+    died_events.send(BirdDied {
+      bird,
+      cause: kind,
+      score: **score,
+    });
   }
 }
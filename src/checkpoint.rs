@@ -1,37 +1,56 @@
 use bevy::prelude::*;
 
-use crate::bird::Bird;
 #[cfg(debug_assertions)]
 use crate::RESOLUTION;
+use crate::{
+  bird::{Bird, Dead},
+  physics::{PhysicsSet, Position},
+  state::GameState,
+};
 
 pub struct CheckpointPlugin;
 
 impl Plugin for CheckpointPlugin {
   fn build(&self, app: &mut App) {
-    app
-      .add_event::<CheckpointPassed>()
-      .add_systems(FixedUpdate, update_checkpoints);
+    app.add_event::<CheckpointPassed>().add_systems(
+      FixedUpdate,
+      update_checkpoints
+        .in_set(PhysicsSet::Collision)
+        .run_if(in_state(GameState::Going)),
+    );
 
     #[cfg(debug_assertions)]
-    app.add_plugins(debug::CheckpointDebugPlugin);
+    if app.is_plugin_added::<bevy::gizmos::GizmoPlugin>() {
+      app.add_plugins(debug::CheckpointDebugPlugin);
+    }
   }
 }
 
 #[derive(Component)]
 pub struct Checkpoint;
 
+/// Sent for every bird that gets past a pair of pipes.
 #[derive(Event)]
-pub struct CheckpointPassed;
+pub struct CheckpointPassed {
+  pub bird: Entity,
+}
 
 fn update_checkpoints(
   mut commands: Commands,
   mut events: EventWriter<CheckpointPassed>,
-  bird_transform: Single<&Transform, With<Bird>>,
-  mut checkpoints: Query<(Entity, &Transform), With<Checkpoint>>,
+  birds: Query<(Entity, &Position), (With<Bird>, Without<Dead>)>,
+  checkpoints: Query<(Entity, &Position), With<Checkpoint>>,
 ) {
-  for (checkpoint, checkpoint_transform) in &mut checkpoints {
-    if bird_transform.translation.x > checkpoint_transform.translation.x {
-      events.send(CheckpointPassed);
+  for (checkpoint, checkpoint_position) in &checkpoints {
+    // birds fly at the same x, so the ones still alive get past together
+    let mut passed = false;
+    for (bird, bird_position) in &birds {
+      if bird_position.x > checkpoint_position.x {
+        events.send(CheckpointPassed { bird });
+        passed = true;
+      }
+    }
+    if passed {
       commands.entity(checkpoint).remove::<Checkpoint>();
     }
   }
@@ -1,61 +1,485 @@
 use bevy::{
-  math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume},
+  math::bounding::{Aabb2d, BoundingVolume},
   prelude::*,
+  utils::HashSet,
 };
 
+use crate::physics::{PhysicsSet, Position, PreviousPosition};
+
 pub struct CollisionPlugin;
 
 impl Plugin for CollisionPlugin {
   fn build(&self, app: &mut App) {
+    app
+      .init_resource::<Contacts>()
+      .add_event::<CollisionStarted>()
+      .add_event::<CollisionEnded>()
+      .add_systems(FixedUpdate, update_contacts.in_set(PhysicsSet::Collision));
+
     #[cfg(debug_assertions)]
-    app.add_plugins(debug::CollisionDebugPlugin);
+    if app.is_plugin_added::<bevy::gizmos::GizmoPlugin>() {
+      app.add_plugins(debug::CollisionDebugPlugin);
+    }
   }
 }
 
-#[derive(Component, Clone, Copy, PartialEq, Debug)]
+/// Shape of a body, relative to its center.
+#[derive(Component, Clone, PartialEq, Debug)]
+#[require(CollisionLayers)]
 pub enum Shape {
   Rectangle(Rectangle),
   Circle(Circle),
+  /// A rectangle with round ends, standing along the y axis.
+  Capsule(Capsule2d),
+  /// A convex polygon. Its vertices may go around it in either direction.
+  Polygon(BoxedPolygon),
+  /// Several shapes, each placed relative to the center of the body.
+  Compound(Vec<(Isometry2d, Shape)>),
+}
+
+/// Which layers a body is on, and which layers it collides with. Two bodies
+/// only collide if each of them is on a layer the other collides with.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CollisionLayers {
+  pub memberships: u32,
+  pub filters: u32,
+}
+
+impl CollisionLayers {
+  pub const ALL: u32 = u32::MAX;
+  pub const BIRD: u32 = 1 << 0;
+  pub const OBSTACLE: u32 = 1 << 1;
+
+  pub const fn new(memberships: u32, filters: u32) -> Self {
+    Self {
+      memberships,
+      filters,
+    }
+  }
+
+  pub fn interacts(self, other: Self) -> bool {
+    self.memberships & other.filters != 0
+      && other.memberships & self.filters != 0
+  }
+}
+
+impl Default for CollisionLayers {
+  fn default() -> Self {
+    Self::new(Self::ALL, Self::ALL)
+  }
 }
 
+/// Sent on the first fixed tick two bodies touch. Bodies that passed through
+/// each other within the tick touch too, with the contact measured along the
+/// way they moved.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CollisionStarted {
+  pub a: Entity,
+  pub b: Entity,
+  pub contact: Contact,
+}
+
+/// Sent on the first fixed tick two bodies stop touching, or one of them
+/// stops existing.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CollisionEnded {
+  pub a: Entity,
+  pub b: Entity,
+}
+
+/// How two colliders overlap.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Contact {
+  /// Direction from the first collider to the second, along which they
+  /// overlap the least.
+  pub normal: Vec2,
+  /// How far the colliders need to move apart along `normal` to only touch.
+  pub depth: f32,
+}
+
+/// Pairs of bodies that touched on the last fixed tick.
+#[derive(Resource, Default, Debug)]
+struct Contacts(HashSet<(Entity, Entity)>);
+
 impl Shape {
-  pub fn to_collider(self, center: Vec2) -> Collider {
+  /// Places the shape at `center`, turned by the rotation of `rotation`
+  /// around the z axis.
+  pub fn to_collider(&self, center: Vec2, rotation: Quat) -> Collider {
+    let (angle, _, _) = rotation.to_euler(EulerRot::ZYX);
+    self.place(Isometry2d::new(center, Rot2::radians(angle)))
+  }
+
+  fn place(&self, isometry: Isometry2d) -> Collider {
     match self {
       Shape::Rectangle(r) => {
-        Collider::Rectangle(Aabb2d::new(center, r.half_size))
+        let Vec2 { x, y } = r.half_size;
+        let corners = [
+          Vec2::new(-x, -y),
+          Vec2::new(x, -y),
+          Vec2::new(x, y),
+          Vec2::new(-x, y),
+        ];
+        Collider::convex(corners.map(|corner| isometry * corner), 0.0)
       }
-      Shape::Circle(c) => {
-        Collider::Circle(BoundingCircle::new(center, c.radius))
+      Shape::Circle(c) => Collider::convex([isometry.translation], c.radius),
+      Shape::Capsule(c) => {
+        let ends = [-c.half_length, c.half_length];
+        Collider::convex(ends.map(|y| isometry * Vec2::new(0.0, y)), c.radius)
       }
+      Shape::Polygon(p) => Collider::convex(
+        p.vertices.iter().map(|&vertex| isometry * vertex),
+        0.0,
+      ),
+      Shape::Compound(parts) => Collider::Compound(
+        parts
+          .iter()
+          .map(|(offset, shape)| shape.place(isometry * *offset))
+          .collect(),
+      ),
     }
   }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// A [`Shape`] placed in the world.
+#[derive(Clone, Debug)]
 pub enum Collider {
-  Rectangle(Aabb2d),
-  Circle(BoundingCircle),
+  /// Points no further than `radius` from the convex hull of `points`.
+  /// A single point makes a circle, and two points make a capsule.
+  Convex {
+    points: Vec<Vec2>,
+    radius: f32,
+  },
+  Compound(Vec<Collider>),
 }
 
 impl Collider {
-  pub fn collides(&self, other: &Collider) -> bool {
+  fn convex(points: impl IntoIterator<Item = Vec2>, radius: f32) -> Self {
+    Collider::Convex {
+      points: points.into_iter().collect(),
+      radius,
+    }
+  }
+
+  /// Smallest axis-aligned box that holds the collider.
+  pub fn aabb(&self) -> Aabb2d {
+    let empty = Aabb2d {
+      min: Vec2::INFINITY,
+      max: Vec2::NEG_INFINITY,
+    };
     match self {
-      Collider::Rectangle(aabb2d) => match other {
-        Collider::Rectangle(other_aabb2d) => aabb2d.intersects(other_aabb2d),
-        Collider::Circle(other_bounding_circle) => {
-          aabb2d.intersects(other_bounding_circle)
-        }
-      },
-      Collider::Circle(bounding_circle) => match other {
-        Collider::Rectangle(other_aabb2d) => {
-          bounding_circle.intersects(other_aabb2d)
-        }
-        Collider::Circle(other_bounding_circle) => {
-          bounding_circle.intersects(other_bounding_circle)
+      Collider::Convex { points, radius } => {
+        let aabb = points.iter().fold(empty, |aabb, &point| Aabb2d {
+          min: aabb.min.min(point),
+          max: aabb.max.max(point),
+        });
+        aabb.grow(Vec2::splat(*radius))
+      }
+      Collider::Compound(parts) => parts
+        .iter()
+        .map(Collider::aabb)
+        .fold(empty, |aabb, part| aabb.merge(&part)),
+    }
+  }
+
+  /// Everything the collider passes through on its way to where it is from
+  /// `motion` away, so that fast bodies can't skip past thin ones.
+  pub fn swept(&self, motion: Vec2) -> Collider {
+    match self {
+      Collider::Convex { points, radius } if motion != Vec2::ZERO => {
+        Collider::Convex {
+          points: convex_hull(
+            points.iter().flat_map(|&point| [point - motion, point]),
+          ),
+          radius: *radius,
         }
-      },
+      }
+      Collider::Convex { .. } => self.clone(),
+      Collider::Compound(parts) => Collider::Compound(
+        parts.iter().map(|part| part.swept(motion)).collect(),
+      ),
     }
   }
+
+  pub fn collides(&self, other: &Collider) -> bool {
+    self.contact(other).is_some()
+  }
+
+  /// Finds how the colliders overlap, if they touch. Compound colliders report
+  /// their deepest contact.
+  pub fn contact(&self, other: &Collider) -> Option<Contact> {
+    let deepest = |a: &Contact, b: &Contact| a.depth.total_cmp(&b.depth);
+    match (self, other) {
+      (Collider::Compound(parts), other) => parts
+        .iter()
+        .filter_map(|part| part.contact(other))
+        .max_by(deepest),
+      (this, Collider::Compound(parts)) => parts
+        .iter()
+        .filter_map(|part| this.contact(part))
+        .max_by(deepest),
+      (
+        Collider::Convex {
+          points: a,
+          radius: radius_a,
+        },
+        Collider::Convex {
+          points: b,
+          radius: radius_b,
+        },
+      ) => convex_contact(a, b, radius_a + radius_b),
+    }
+  }
+}
+
+fn convex_contact(a: &[Vec2], b: &[Vec2], radius: f32) -> Option<Contact> {
+  // one hull may lie inside the other without their edges meeting
+  let overlapping = b.first().is_some_and(|&point| contains(a, point))
+    || a.first().is_some_and(|&point| contains(b, point));
+  let ClosestPoints {
+    distance_squared,
+    a: closest_a,
+    b: closest_b,
+  } = closest_points(a, b);
+  if !overlapping && distance_squared > 0.0 {
+    if distance_squared > radius.powi(2) {
+      return None;
+    }
+    let distance = distance_squared.sqrt();
+    return Some(Contact {
+      normal: (closest_b - closest_a) / distance,
+      depth: radius - distance,
+    });
+  }
+  // the hulls overlap themselves, so they're separated along the edge normal
+  // they overlap the least along
+  let axes = edges(a)
+    .chain(edges(b))
+    .filter_map(|(p, q)| (q - p).perp().try_normalize());
+  let contact = axes
+    .flat_map(|axis| {
+      let (min_a, max_a) = project(a, axis);
+      let (min_b, max_b) = project(b, axis);
+      [(axis, max_a - min_b), (-axis, max_b - min_a)]
+    })
+    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    .map(|(normal, overlap)| Contact {
+      normal,
+      depth: overlap + radius,
+    });
+  // hulls without edges are single points, here at the same place
+  contact.or(Some(Contact {
+    normal: Vec2::Y,
+    depth: radius,
+  }))
+}
+
+/// Smallest convex polygon that holds all the points, going around it
+/// counter-clockwise.
+fn convex_hull(points: impl IntoIterator<Item = Vec2>) -> Vec<Vec2> {
+  let mut points: Vec<_> = points.into_iter().collect();
+  points.sort_unstable_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+  points.dedup();
+  if points.len() < 3 {
+    return points;
+  }
+  let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() + 1);
+  let turns_left = |hull: &[Vec2], point: Vec2| match hull {
+    [.., a, b] => (*b - *a).perp_dot(point - *a) > 0.0,
+    _ => true,
+  };
+  // the lower half goes left to right, and the upper half back
+  for &point in &points {
+    while hull.len() >= 2 && !turns_left(&hull, point) {
+      hull.pop();
+    }
+    hull.push(point);
+  }
+  let lower_len = hull.len();
+  for &point in points.iter().rev().skip(1) {
+    while hull.len() > lower_len && !turns_left(&hull, point) {
+      hull.pop();
+    }
+    hull.push(point);
+  }
+  // the last point is the first one again
+  hull.pop();
+  hull
+}
+
+/// Edges of a convex hull. A single point is an edge of zero length.
+fn edges(points: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+  let count = if points.len() == 2 { 1 } else { points.len() };
+  let next = points.iter().cycle().skip(1);
+  points.iter().copied().zip(next.copied()).take(count)
+}
+
+fn contains(points: &[Vec2], point: Vec2) -> bool {
+  if points.len() < 3 {
+    return false;
+  }
+  let sides = || edges(points).map(|(a, b)| (b - a).perp_dot(point - a));
+  sides().all(|side| side >= 0.0) || sides().all(|side| side <= 0.0)
+}
+
+fn project(points: &[Vec2], axis: Vec2) -> (f32, f32) {
+  points.iter().map(|point| point.dot(axis)).fold(
+    (f32::INFINITY, f32::NEG_INFINITY),
+    |(min, max), distance| (min.min(distance), max.max(distance)),
+  )
+}
+
+/// A point on each of two hulls, and the squared distance between them.
+#[derive(Clone, Copy, Debug)]
+struct ClosestPoints {
+  distance_squared: f32,
+  a: Vec2,
+  b: Vec2,
+}
+
+impl ClosestPoints {
+  const FAR: Self = Self {
+    distance_squared: f32::INFINITY,
+    a: Vec2::ZERO,
+    b: Vec2::ZERO,
+  };
+
+  fn new(a: Vec2, b: Vec2) -> Self {
+    Self {
+      distance_squared: a.distance_squared(b),
+      a,
+      b,
+    }
+  }
+
+  fn nearest(self, other: Self) -> Self {
+    if other.distance_squared < self.distance_squared {
+      other
+    } else {
+      self
+    }
+  }
+}
+
+/// Closest points on the edges of two convex hulls.
+fn closest_points(a: &[Vec2], b: &[Vec2]) -> ClosestPoints {
+  edges(a)
+    .flat_map(|edge| edges(b).map(move |other| (edge, other)))
+    .map(|(edge, other)| closest_points_on_segments(edge, other))
+    .fold(ClosestPoints::FAR, ClosestPoints::nearest)
+}
+
+fn closest_points_on_segments(
+  (a0, a1): (Vec2, Vec2),
+  (b0, b1): (Vec2, Vec2),
+) -> ClosestPoints {
+  let (a, b) = (a1 - a0, b1 - b0);
+  let crosses = |p0: Vec2, p: Vec2, q0: Vec2, q1: Vec2| {
+    p.perp_dot(q0 - p0) * p.perp_dot(q1 - p0) < 0.0
+  };
+  if crosses(a0, a, b0, b1) && crosses(b0, b, a0, a1) {
+    let point = a0 + a * (b0 - a0).perp_dot(b) / a.perp_dot(b);
+    return ClosestPoints::new(point, point);
+  }
+  [
+    ClosestPoints::new(a0, closest_point_on_segment(a0, (b0, b1))),
+    ClosestPoints::new(a1, closest_point_on_segment(a1, (b0, b1))),
+    ClosestPoints::new(closest_point_on_segment(b0, (a0, a1)), b0),
+    ClosestPoints::new(closest_point_on_segment(b1, (a0, a1)), b1),
+  ]
+  .into_iter()
+  .fold(ClosestPoints::FAR, ClosestPoints::nearest)
+}
+
+fn closest_point_on_segment(point: Vec2, (a, b): (Vec2, Vec2)) -> Vec2 {
+  let ab = b - a;
+  let length_squared = ab.length_squared();
+  let t = if length_squared > 0.0 {
+    ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0)
+  } else {
+    0.0
+  };
+  a + ab * t
+}
+
+/// Finds the pairs of boxes that overlap, each listed once with the lower
+/// index first.
+///
+/// Boxes are swept along the x axis, so only boxes whose x ranges overlap get
+/// tested against each other. Bodies of a side-scroller are spread along x,
+/// which leaves few of them to test.
+pub fn sweep_and_prune(aabbs: &[Aabb2d]) -> Vec<(usize, usize)> {
+  let mut order: Vec<usize> = (0..aabbs.len()).collect();
+  order.sort_unstable_by(|&a, &b| aabbs[a].min.x.total_cmp(&aabbs[b].min.x));
+  let mut pairs = Vec::new();
+  let mut active: Vec<usize> = Vec::new();
+  for i in order {
+    let aabb = aabbs[i];
+    active.retain(|&j| aabbs[j].max.x >= aabb.min.x);
+    for &j in &active {
+      if aabbs[j].min.y <= aabb.max.y && aabb.min.y <= aabbs[j].max.y {
+        pairs.push((i.min(j), i.max(j)));
+      }
+    }
+    active.push(i);
+  }
+  pairs
+}
+
+fn update_contacts(
+  mut contacts: ResMut<Contacts>,
+  mut started_events: EventWriter<CollisionStarted>,
+  mut ended_events: EventWriter<CollisionEnded>,
+  query: Query<(
+    Entity,
+    &Shape,
+    &CollisionLayers,
+    &Position,
+    Option<&PreviousPosition>,
+    &Transform,
+  )>,
+) {
+  let mut bodies = Vec::new();
+  let mut aabbs = Vec::new();
+  for (entity, shape, layers, position, previous_position, transform) in &query
+  {
+    let collider = shape.to_collider(**position, transform.rotation);
+    let motion = previous_position.map_or(Vec2::ZERO, |previous_position| {
+      **position - **previous_position
+    });
+    // bodies can touch anywhere along the way they moved this tick
+    let aabb = collider.aabb();
+    aabbs.push(aabb.merge(&aabb.translated_by(-motion)));
+    bodies.push((entity, *layers, collider, motion));
+  }
+  let mut touching = HashSet::new();
+  for (i, j) in sweep_and_prune(&aabbs) {
+    let (entity_a, layers_a, collider_a, motion_a) = &bodies[i];
+    let (entity_b, layers_b, collider_b, motion_b) = &bodies[j];
+    if !layers_a.interacts(*layers_b) {
+      continue;
+    }
+    // moving one body by the motion of both relative to each other is the
+    // same as moving them both
+    let swept = collider_a.swept(*motion_a - *motion_b);
+    let Some(mut contact) = swept.contact(collider_b) else {
+      continue;
+    };
+    // pairs are kept in the same order whatever order they're queried in
+    let (a, b) = if entity_a < entity_b {
+      (*entity_a, *entity_b)
+    } else {
+      contact.normal = -contact.normal;
+      (*entity_b, *entity_a)
+    };
+    if !contacts.0.contains(&(a, b)) {
+      started_events.send(CollisionStarted { a, b, contact });
+    }
+    touching.insert((a, b));
+  }
+  for &(a, b) in contacts.0.difference(&touching) {
+    ended_events.send(CollisionEnded { a, b });
+  }
+  contacts.0 = touching;
 }
 
 #[cfg(debug_assertions)]
@@ -72,15 +496,329 @@ mod debug {
 
   fn debug_shapes(mut gizmos: Gizmos, query: Query<(&Shape, &Transform)>) {
     for (shape, transform) in &query {
-      let isometry = Isometry2d::from(transform.translation.xy());
-      match shape {
-        Shape::Rectangle(r) => {
-          gizmos.primitive_2d(r, isometry, Color::srgb(1.0, 0.0, 0.0));
-        }
-        Shape::Circle(c) => {
-          gizmos.primitive_2d(c, isometry, Color::srgb(0.0, 0.0, 1.0));
+      let (angle, _, _) = transform.rotation.to_euler(EulerRot::ZYX);
+      let isometry =
+        Isometry2d::new(transform.translation.xy(), Rot2::radians(angle));
+      draw_shape(&mut gizmos, shape, isometry);
+    }
+  }
+
+  fn draw_shape(gizmos: &mut Gizmos, shape: &Shape, isometry: Isometry2d) {
+    let color = Color::srgb(1.0, 0.0, 0.0);
+    match shape {
+      Shape::Rectangle(r) => {
+        gizmos.primitive_2d(r, isometry, color);
+      }
+      Shape::Circle(c) => {
+        gizmos.primitive_2d(c, isometry, Color::srgb(0.0, 0.0, 1.0));
+      }
+      Shape::Capsule(c) => {
+        gizmos.primitive_2d(c, isometry, color);
+      }
+      Shape::Polygon(p) => {
+        gizmos.primitive_2d(p, isometry, color);
+      }
+      Shape::Compound(parts) => {
+        for (offset, part) in parts {
+          draw_shape(gizmos, part, isometry * *offset);
         }
       }
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use std::f32::consts::FRAC_PI_2;
+
+  use super::*;
+
+  fn rectangle(width: f32, height: f32) -> Shape {
+    Shape::Rectangle(Rectangle::new(width, height))
+  }
+
+  fn circle(radius: f32) -> Shape {
+    Shape::Circle(Circle::new(radius))
+  }
+
+  fn place(shape: &Shape, center: Vec2) -> Collider {
+    shape.to_collider(center, Quat::IDENTITY)
+  }
+
+  fn assert_contact(contact: Option<Contact>, normal: Vec2, depth: f32) {
+    let contact = contact.expect("the colliders should touch");
+    assert!(
+      contact.normal.abs_diff_eq(normal, 1e-4),
+      "normal {} isn't {normal}",
+      contact.normal,
+    );
+    assert!(
+      (contact.depth - depth).abs() < 1e-4,
+      "depth {} isn't {depth}",
+      contact.depth,
+    );
+  }
+
+  #[test]
+  fn circle_touches_rectangle_side() {
+    let square = place(&rectangle(20.0, 20.0), Vec2::ZERO);
+    let touching = place(&circle(5.0), Vec2::new(12.0, 0.0));
+    assert_contact(square.contact(&touching), Vec2::X, 3.0);
+    let apart = place(&circle(5.0), Vec2::new(16.0, 0.0));
+    assert!(!square.collides(&apart));
+  }
+
+  #[test]
+  fn circle_misses_rectangle_corner() {
+    let square = place(&rectangle(20.0, 20.0), Vec2::ZERO);
+    // the bounding boxes of these overlap, but the shapes don't
+    let apart = place(&circle(5.0), Vec2::new(14.0, 14.0));
+    assert!(!square.collides(&apart));
+    let touching = place(&circle(5.0), Vec2::new(13.0, 13.0));
+    let depth = 5.0 - 3.0 * 2.0_f32.sqrt();
+    assert_contact(square.contact(&touching), Vec2::ONE.normalize(), depth);
+  }
+
+  #[test]
+  fn rectangle_turns_with_rotation() {
+    let bar = rectangle(20.0, 4.0);
+    let rotation = Quat::from_rotation_z(FRAC_PI_2);
+    let standing = bar.to_collider(Vec2::ZERO, rotation);
+    let beside = place(&circle(2.0), Vec2::new(8.0, 0.0));
+    assert!(place(&bar, Vec2::ZERO).collides(&beside));
+    assert!(!standing.collides(&beside));
+    let above = place(&circle(2.0), Vec2::new(0.0, 11.0));
+    assert_contact(standing.contact(&above), Vec2::Y, 1.0);
+  }
+
+  #[test]
+  fn rotated_rectangles_touch_at_corner() {
+    let square = place(&rectangle(20.0, 20.0), Vec2::ZERO);
+    let rotation = Quat::from_rotation_z(FRAC_PI_2 / 2.0);
+    // a diamond with its left corner sqrt(200) away from its center
+    let half_diagonal = 200.0_f32.sqrt();
+    let diamond = rectangle(20.0, 20.0)
+      .to_collider(Vec2::new(9.0 + half_diagonal, 0.0), rotation);
+    assert_contact(square.contact(&diamond), Vec2::X, 1.0);
+    let diamond = rectangle(20.0, 20.0)
+      .to_collider(Vec2::new(11.0 + half_diagonal, 0.0), rotation);
+    assert!(!square.collides(&diamond));
+  }
+
+  #[test]
+  fn capsule_has_round_ends() {
+    let capsule = place(&Shape::Capsule(Capsule2d::new(5.0, 20.0)), Vec2::ZERO);
+    let on_top = place(&circle(1.0), Vec2::new(0.0, 15.5));
+    assert_contact(capsule.contact(&on_top), Vec2::Y, 0.5);
+    // in the corner of the box around the capsule, beyond its end cap
+    let off_corner = place(&circle(0.5), Vec2::new(4.5, 14.5));
+    assert!(!capsule.collides(&off_corner));
+    let beside = place(&circle(1.0), Vec2::new(5.5, 0.0));
+    assert_contact(capsule.contact(&beside), Vec2::X, 0.5);
+  }
+
+  #[test]
+  fn compound_touches_with_any_part() {
+    // an L made of a post and a foot sticking out to the right
+    let l = Shape::Compound(vec![
+      (Isometry2d::IDENTITY, rectangle(10.0, 40.0)),
+      (Isometry2d::from_xy(15.0, -15.0), rectangle(20.0, 10.0)),
+    ]);
+    let l = place(&l, Vec2::ZERO);
+    let in_notch = place(&circle(2.0), Vec2::new(15.0, 5.0));
+    assert!(!l.collides(&in_notch));
+    let on_foot = place(&circle(2.0), Vec2::new(15.0, -9.0));
+    assert_contact(l.contact(&on_foot), Vec2::Y, 1.0);
+    let on_post = place(&circle(2.0), Vec2::new(0.0, 21.0));
+    assert_contact(l.contact(&on_post), Vec2::Y, 1.0);
+  }
+
+  #[test]
+  fn compound_reports_deepest_contact() {
+    let pair = Shape::Compound(vec![
+      (Isometry2d::from_xy(-6.0, 0.0), circle(5.0)),
+      (Isometry2d::from_xy(6.0, 0.0), circle(5.0)),
+    ]);
+    let pair = place(&pair, Vec2::ZERO);
+    // sticks 1 into the left circle and 3 into the right one
+    let between = place(&rectangle(4.0, 4.0), Vec2::new(2.0, 0.0));
+    assert_contact(pair.contact(&between), -Vec2::X, 3.0);
+  }
+
+  #[test]
+  fn shape_inside_another_touches() {
+    let wide = place(&rectangle(40.0, 20.0), Vec2::ZERO);
+    let inner_circle = place(&circle(2.0), Vec2::new(0.0, 3.0));
+    assert_contact(wide.contact(&inner_circle), Vec2::Y, 9.0);
+    let inner_square = place(&rectangle(4.0, 4.0), Vec2::new(0.0, 3.0));
+    assert_contact(wide.contact(&inner_square), Vec2::Y, 9.0);
+    assert_contact(inner_square.contact(&wide), -Vec2::Y, 9.0);
+  }
+
+  #[test]
+  fn swapping_colliders_flips_normal() {
+    let square = place(&rectangle(20.0, 20.0), Vec2::ZERO);
+    let ball = place(&circle(5.0), Vec2::new(-3.0, 12.0));
+    let contact = square.contact(&ball).unwrap();
+    let flipped = ball.contact(&square).unwrap();
+    assert_eq!(contact.normal, Vec2::Y);
+    assert_eq!(flipped.normal, -contact.normal);
+    assert_eq!(flipped.depth, contact.depth);
+    assert!(contact.depth > 0.0);
+  }
+
+  fn contacts_app() -> App {
+    let mut app = App::new();
+    app
+      .init_resource::<Contacts>()
+      .add_event::<CollisionStarted>()
+      .add_event::<CollisionEnded>()
+      .add_systems(Update, update_contacts);
+    app
+  }
+
+  fn spawn_body(app: &mut App, layers: CollisionLayers, x: f32) -> Entity {
+    let body = (
+      circle(5.0),
+      layers,
+      Position(Vec2::new(x, 0.0)),
+      Transform::default(),
+    );
+    app.world_mut().spawn(body).id()
+  }
+
+  /// Updates the app and returns the collisions that started and ended.
+  fn update(app: &mut App) -> (Vec<CollisionStarted>, Vec<CollisionEnded>) {
+    app.update();
+    let world = app.world_mut();
+    let mut started = world.resource_mut::<Events<CollisionStarted>>();
+    let started = started.drain().collect();
+    let mut ended = world.resource_mut::<Events<CollisionEnded>>();
+    (started, ended.drain().collect())
+  }
+
+  const BIRD: CollisionLayers =
+    CollisionLayers::new(CollisionLayers::BIRD, CollisionLayers::OBSTACLE);
+  const OBSTACLE: CollisionLayers =
+    CollisionLayers::new(CollisionLayers::OBSTACLE, CollisionLayers::BIRD);
+
+  #[test]
+  fn collisions_start_and_end_once() {
+    let mut app = contacts_app();
+    let bird = spawn_body(&mut app, BIRD, 0.0);
+    let obstacle = spawn_body(&mut app, OBSTACLE, 8.0);
+    let (started, ended) = update(&mut app);
+    assert_eq!(started.len(), 1);
+    assert!(ended.is_empty());
+    let CollisionStarted { a, b, contact } = started[0];
+    assert_eq!((a, b), (bird, obstacle));
+    assert_contact(Some(contact), Vec2::X, 2.0);
+    // still touching
+    let (started, ended) = update(&mut app);
+    assert!(started.is_empty() && ended.is_empty());
+    app.world_mut().get_mut::<Position>(obstacle).unwrap().x = 20.0;
+    let (started, ended) = update(&mut app);
+    assert!(started.is_empty());
+    assert_eq!(ended.len(), 1);
+    assert_eq!((ended[0].a, ended[0].b), (bird, obstacle));
+    let (started, ended) = update(&mut app);
+    assert!(started.is_empty() && ended.is_empty());
+  }
+
+  #[test]
+  fn collision_ends_when_body_is_gone() {
+    let mut app = contacts_app();
+    spawn_body(&mut app, BIRD, 0.0);
+    let obstacle = spawn_body(&mut app, OBSTACLE, 0.0);
+    assert_eq!(update(&mut app).0.len(), 1);
+    app.world_mut().despawn(obstacle);
+    let (started, ended) = update(&mut app);
+    assert!(started.is_empty());
+    assert_eq!(ended.len(), 1);
+  }
+
+  #[test]
+  fn layers_filter_pairs() {
+    let mut app = contacts_app();
+    spawn_body(&mut app, BIRD, 0.0);
+    spawn_body(&mut app, BIRD, 2.0);
+    let (started, _) = update(&mut app);
+    assert!(started.is_empty());
+    let obstacle = spawn_body(&mut app, OBSTACLE, 1.0);
+    let (started, _) = update(&mut app);
+    assert_eq!(started.len(), 2);
+    assert!(started.iter().all(|started| started.b == obstacle));
+    // bodies on no layers the other collides with are left alone too
+    let ghost = CollisionLayers::new(CollisionLayers::BIRD, 0);
+    spawn_body(&mut app, ghost, 1.0);
+    let (started, _) = update(&mut app);
+    assert!(started.is_empty());
+  }
+
+  #[test]
+  fn fast_body_hits_thin_wall_within_tick() {
+    let mut app = contacts_app();
+    let wall = (
+      rectangle(2.0, 40.0),
+      OBSTACLE,
+      Position(Vec2::ZERO),
+      Transform::default(),
+    );
+    app.world_mut().spawn(wall);
+    // from one side of the wall to the other, touching it at neither end
+    let bird = (
+      circle(2.0),
+      BIRD,
+      Position(Vec2::new(20.0, 0.0)),
+      PreviousPosition(Vec2::new(-20.0, 0.0)),
+      Transform::default(),
+    );
+    let bird = app.world_mut().spawn(bird).id();
+    let (started, _) = update(&mut app);
+    assert_eq!(started.len(), 1);
+    // passing over the wall misses it
+    let mut bird = app.world_mut().entity_mut(bird);
+    bird.insert((
+      Position(Vec2::new(20.0, 30.0)),
+      PreviousPosition(Vec2::new(-20.0, 30.0)),
+    ));
+    let (_, ended) = update(&mut app);
+    assert_eq!(ended.len(), 1);
+  }
+
+  #[test]
+  fn hull_drops_collinear_points() {
+    let hull = convex_hull([
+      Vec2::new(0.0, 0.0),
+      Vec2::new(1.0, 0.0),
+      Vec2::new(2.0, 0.0),
+      Vec2::new(2.0, 1.0),
+      Vec2::new(2.0, 2.0),
+      Vec2::new(1.0, 2.0),
+      Vec2::new(0.0, 2.0),
+      Vec2::new(0.0, 1.0),
+      Vec2::new(1.0, 1.0),
+    ]);
+    let square = [
+      Vec2::new(0.0, 0.0),
+      Vec2::new(2.0, 0.0),
+      Vec2::new(2.0, 2.0),
+      Vec2::new(0.0, 2.0),
+    ];
+    assert_eq!(hull, square);
+  }
+
+  #[test]
+  fn hull_of_points_on_a_line_is_segment() {
+    let points = (0..5).map(|i| Vec2::splat(i as f32));
+    assert_eq!(convex_hull(points), [Vec2::ZERO, Vec2::splat(4.0)]);
+  }
+
+  #[test]
+  fn hull_ignores_duplicates() {
+    let triangle = [Vec2::ZERO, Vec2::X, Vec2::Y];
+    let hull = convex_hull(triangle.iter().chain(&triangle).copied());
+    assert_eq!(hull, triangle);
+    assert_eq!(convex_hull([Vec2::ONE; 3]), [Vec2::ONE]);
+  }
+}
@@ -0,0 +1,374 @@
+use std::io::{self, ErrorKind};
+
+use bevy::{
+  asset::{io::Reader, AssetLoader, LoadContext},
+  prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::pipe::PipePattern;
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+  fn build(&self, app: &mut App) {
+    app.init_resource::<GameConfig>();
+    // without an asset server the game runs on the inserted or default config
+    if app.is_plugin_added::<AssetPlugin>() {
+      app
+        .init_asset::<GameConfig>()
+        .init_asset_loader::<GameConfigLoader>()
+        .add_systems(Startup, load_config)
+        .add_systems(PreUpdate, apply_loaded_config);
+    }
+  }
+}
+
+/// Tuning of the game. It's loaded from `assets/game.config.ron` and reloaded
+/// whenever the file changes.
+#[derive(Asset, Resource, TypePath, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GameConfig {
+  /// Horizontal speed of pipes and ground.
+  pub game_speed: f32,
+  pub bird: BirdConfig,
+  pub pipe: PipeConfig,
+  pub ground: GroundConfig,
+  pub difficulty: DifficultyConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct BirdConfig {
+  /// Vertical velocity the bird gets when it flaps.
+  pub flap_force: f32,
+  /// Time after a flap during which the bird can't flap again.
+  pub flap_cooldown_secs: f32,
+  /// How long a press during the cooldown is kept to flap once it's over.
+  pub flap_buffer_secs: f32,
+  /// Whether holding the flap button keeps the bird going up for up to
+  /// [`BirdConfig::max_hold_secs`].
+  pub hold_to_flap_higher: bool,
+  pub max_hold_secs: f32,
+  pub gravity: f32,
+  /// How much vertical velocity turns the bird by a degree.
+  pub velocity_to_angle_ratio: f32,
+  pub hitbox_radius: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PipeConfig {
+  /// Height of the gap between top and bottom pipes.
+  pub vertical_gap: f32,
+  /// How far from the middle of the screen gaps can appear.
+  pub spawn_point_mid_distance: f32,
+  pub respawn_cooldown_secs: f32,
+  /// Patterns new pairs of pipes pick from. Falls back to
+  /// [`PipePattern::Static`] if the list is empty.
+  pub patterns: Vec<WeightedPipePattern>,
+}
+
+/// A pipe pattern and how likely it is to be picked relative to the others.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct WeightedPipePattern {
+  pub weight: f32,
+  pub pattern: PipePattern,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GroundConfig {
+  /// Height of the ground.
+  pub level: f32,
+}
+
+/// How the game gets harder over the course of a run.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DifficultyConfig {
+  pub driver: DifficultyDriver,
+  /// Points of the difficulty curve, sorted by [`DifficultyKeyframe::at`]
+  /// when the config loads. The difficulty is interpolated in between them
+  /// and stays the same past the last one.
+  pub curve: Vec<DifficultyKeyframe>,
+}
+
+impl DifficultyConfig {
+  /// Sorts the curve by [`DifficultyKeyframe::at`], keeping only the first
+  /// keyframe at any one point.
+  pub fn sort_curve(&mut self) {
+    self.curve.sort_by(|a, b| a.at.total_cmp(&b.at));
+    self.curve.dedup_by(|later, earlier| later.at == earlier.at);
+  }
+}
+
+/// What moves the run along the difficulty curve.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DifficultyDriver {
+  Score,
+  /// Seconds the run has been going for.
+  Time,
+}
+
+/// Multipliers of the base tuning at some point of a run.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct DifficultyKeyframe {
+  /// Score or seconds, depending on the [`DifficultyDriver`].
+  pub at: f32,
+  pub game_speed: f32,
+  pub vertical_gap: f32,
+  pub respawn_cooldown: f32,
+  pub spawn_point_mid_distance: f32,
+}
+
+impl GameConfig {
+  /// Checks that the game can run on this tuning anywhere along the
+  /// difficulty curve.
+  pub fn validate(&self) -> Result<(), String> {
+    let curve = &self.difficulty.curve;
+    let keyframes = curve
+      .iter()
+      .copied()
+      .chain(curve.is_empty().then(DifficultyKeyframe::default));
+    for keyframe in keyframes {
+      if !keyframe.at.is_finite() {
+        return Err(format!("difficulty keyframe at {}", keyframe.at));
+      }
+      let cooldown =
+        self.pipe.respawn_cooldown_secs * keyframe.respawn_cooldown;
+      if !(cooldown.is_finite() && cooldown > 0.0) {
+        return Err(format!(
+          "pipe respawn cooldown at {} is {cooldown} s",
+          keyframe.at
+        ));
+      }
+      // gaps spawn between the ground and the same distance above the middle
+      let distance =
+        self.pipe.spawn_point_mid_distance * keyframe.spawn_point_mid_distance;
+      if !(distance.is_finite() && distance > self.ground.level / 2.0) {
+        return Err(format!(
+          "spawn point mid distance at {} is {distance}, which leaves no room \
+           for gaps above the ground",
+          keyframe.at
+        ));
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Default for GameConfig {
+  fn default() -> Self {
+    Self {
+      game_speed: 100.0,
+      bird: default(),
+      pipe: default(),
+      ground: default(),
+      difficulty: default(),
+    }
+  }
+}
+
+impl Default for BirdConfig {
+  fn default() -> Self {
+    Self {
+      flap_force: 300.0,
+      flap_cooldown_secs: 0.1,
+      flap_buffer_secs: 0.1,
+      hold_to_flap_higher: false,
+      max_hold_secs: 0.15,
+      gravity: 1800.0,
+      velocity_to_angle_ratio: 8.0,
+      hitbox_radius: 10.0,
+    }
+  }
+}
+
+impl Default for PipeConfig {
+  fn default() -> Self {
+    Self {
+      vertical_gap: 80.0,
+      spawn_point_mid_distance: 115.0,
+      respawn_cooldown_secs: 2.0,
+      patterns: vec![
+        WeightedPipePattern {
+          weight: 6.0,
+          pattern: PipePattern::Static,
+        },
+        WeightedPipePattern {
+          weight: 2.0,
+          pattern: PipePattern::Oscillating {
+            amplitude: 25.0,
+            period_secs: 2.0,
+          },
+        },
+        WeightedPipePattern {
+          weight: 1.0,
+          pattern: PipePattern::Breathing {
+            amplitude: 15.0,
+            period_secs: 1.5,
+          },
+        },
+        WeightedPipePattern {
+          weight: 1.0,
+          pattern: PipePattern::Homing { speed: 20.0 },
+        },
+      ],
+    }
+  }
+}
+
+impl Default for DifficultyConfig {
+  fn default() -> Self {
+    Self {
+      driver: DifficultyDriver::Score,
+      curve: vec![
+        DifficultyKeyframe::default(),
+        DifficultyKeyframe {
+          at: 100.0,
+          game_speed: 1.3,
+          vertical_gap: 0.85,
+          respawn_cooldown: 0.8,
+          spawn_point_mid_distance: 1.2,
+        },
+      ],
+    }
+  }
+}
+
+impl Default for DifficultyKeyframe {
+  fn default() -> Self {
+    Self {
+      at: 0.0,
+      game_speed: 1.0,
+      vertical_gap: 1.0,
+      respawn_cooldown: 1.0,
+      spawn_point_mid_distance: 1.0,
+    }
+  }
+}
+
+impl Default for GroundConfig {
+  fn default() -> Self {
+    Self { level: 50.0 }
+  }
+}
+
+#[derive(Resource, Deref)]
+struct GameConfigHandle(Handle<GameConfig>);
+
+#[derive(Default)]
+struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+  type Asset = GameConfig;
+  type Settings = ();
+  type Error = io::Error;
+
+  async fn load(
+    &self,
+    reader: &mut dyn Reader,
+    _settings: &(),
+    _load_context: &mut LoadContext<'_>,
+  ) -> io::Result<GameConfig> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    ron::de::from_bytes(&bytes)
+      .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+  }
+
+  fn extensions(&self) -> &[&str] {
+    &["config.ron"]
+  }
+}
+
+fn load_config(mut commands: Commands, asset_server: Res<AssetServer>) {
+  commands
+    .insert_resource(GameConfigHandle(asset_server.load("game.config.ron")));
+}
+
+fn apply_loaded_config(
+  mut events: EventReader<AssetEvent<GameConfig>>,
+  handle: Res<GameConfigHandle>,
+  configs: Res<Assets<GameConfig>>,
+  mut config: ResMut<GameConfig>,
+) {
+  for event in events.read() {
+    if event.is_loaded_with_dependencies(handle.id())
+      || event.is_modified(handle.id())
+    {
+      let Some(loaded) = configs.get(handle.id()) else {
+        continue;
+      };
+      let mut loaded = loaded.clone();
+      loaded.difficulty.sort_curve();
+      match loaded.validate() {
+        Ok(()) => {
+          *config = loaded;
+          info!("game config applied");
+        }
+        Err(e) => error!("game config rejected: {e}"),
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn default_config_is_valid() {
+    assert_eq!(GameConfig::default().validate(), Ok(()));
+  }
+
+  #[test]
+  fn unplayable_config_is_invalid() {
+    let mut config = GameConfig::default();
+    config.pipe.respawn_cooldown_secs = -1.0;
+    assert!(config.validate().is_err());
+
+    let mut config = GameConfig::default();
+    config.pipe.respawn_cooldown_secs = f32::NAN;
+    assert!(config.validate().is_err());
+
+    // the gaps would have to spawn inside the ground
+    let mut config = GameConfig::default();
+    config.pipe.spawn_point_mid_distance = config.ground.level / 2.0;
+    assert!(config.validate().is_err());
+
+    // as they would once the curve narrows the range
+    let mut config = GameConfig::default();
+    config.difficulty.curve[1].spawn_point_mid_distance = 0.1;
+    assert!(config.validate().is_err());
+
+    let mut config = GameConfig::default();
+    config.difficulty.curve[1].at = f32::INFINITY;
+    assert!(config.validate().is_err());
+  }
+
+  #[test]
+  fn curve_is_sorted_without_duplicates() {
+    let keyframe = |at, game_speed| DifficultyKeyframe {
+      at,
+      game_speed,
+      ..default()
+    };
+    let mut config = DifficultyConfig {
+      curve: vec![
+        keyframe(50.0, 2.0),
+        keyframe(0.0, 1.0),
+        keyframe(50.0, 3.0),
+        keyframe(10.0, 1.5),
+      ],
+      ..default()
+    };
+    config.sort_curve();
+    assert_eq!(
+      config.curve,
+      [keyframe(0.0, 1.0), keyframe(10.0, 1.5), keyframe(50.0, 2.0)]
+    );
+  }
+}
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+
+use crate::{
+  config::{DifficultyDriver, DifficultyKeyframe, GameConfig},
+  physics::PhysicsSet,
+  score::Score,
+  state::{OnRunStart, RunTicks},
+};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<Difficulty>()
+      .add_systems(OnRunStart, reset_difficulty)
+      .add_systems(FixedUpdate, update_difficulty.before(PhysicsSet::Movement));
+  }
+}
+
+/// Tuning of the current moment of the run: the base config scaled by the
+/// difficulty curve.
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct Difficulty {
+  pub game_speed: f32,
+  pub vertical_gap: f32,
+  pub respawn_cooldown_secs: f32,
+  pub spawn_point_mid_distance: f32,
+}
+
+impl Difficulty {
+  /// Difficulty at the given score or second of a run, depending on the
+  /// configured driver.
+  pub fn at(config: &GameConfig, level: f32) -> Self {
+    let multipliers = sample_curve(&config.difficulty.curve, level);
+    Self {
+      game_speed: config.game_speed * multipliers.game_speed,
+      vertical_gap: config.pipe.vertical_gap * multipliers.vertical_gap,
+      respawn_cooldown_secs: config.pipe.respawn_cooldown_secs
+        * multipliers.respawn_cooldown,
+      spawn_point_mid_distance: config.pipe.spawn_point_mid_distance
+        * multipliers.spawn_point_mid_distance,
+    }
+  }
+}
+
+impl FromWorld for Difficulty {
+  fn from_world(world: &mut World) -> Self {
+    Self::at(world.resource::<GameConfig>(), 0.0)
+  }
+}
+
+fn sample_curve(curve: &[DifficultyKeyframe], at: f32) -> DifficultyKeyframe {
+  let next = curve.partition_point(|keyframe| keyframe.at <= at);
+  match (
+    next.checked_sub(1).map(|i| curve[i]),
+    curve.get(next).copied(),
+  ) {
+    (Some(prev), Some(next)) => {
+      let t = (at - prev.at) / (next.at - prev.at);
+      DifficultyKeyframe {
+        at,
+        game_speed: prev.game_speed.lerp(next.game_speed, t),
+        vertical_gap: prev.vertical_gap.lerp(next.vertical_gap, t),
+        respawn_cooldown: prev.respawn_cooldown.lerp(next.respawn_cooldown, t),
+        spawn_point_mid_distance: prev
+          .spawn_point_mid_distance
+          .lerp(next.spawn_point_mid_distance, t),
+      }
+    }
+    (Some(keyframe), None) | (None, Some(keyframe)) => keyframe,
+    (None, None) => DifficultyKeyframe::default(),
+  }
+}
+
+pub(crate) fn reset_difficulty(
+  config: Res<GameConfig>,
+  mut difficulty: ResMut<Difficulty>,
+) {
+  difficulty.set_if_neq(Difficulty::at(&config, 0.0));
+}
+
+pub(crate) fn update_difficulty(
+  config: Res<GameConfig>,
+  score: Res<Score>,
+  ticks: Res<RunTicks>,
+  fixed_time: Res<Time<Fixed>>,
+  mut difficulty: ResMut<Difficulty>,
+) {
+  let level = match config.difficulty.driver {
+    DifficultyDriver::Score => **score as f32,
+    DifficultyDriver::Time => {
+      **ticks as f32 * fixed_time.timestep().as_secs_f32()
+    }
+  };
+  difficulty.set_if_neq(Difficulty::at(&config, level));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn keyframe(at: f32, game_speed: f32) -> DifficultyKeyframe {
+    DifficultyKeyframe {
+      at,
+      game_speed,
+      ..default()
+    }
+  }
+
+  #[test]
+  fn curve_holds_its_ends() {
+    let curve = [keyframe(10.0, 2.0), keyframe(20.0, 4.0)];
+    assert_eq!(sample_curve(&curve, 0.0), curve[0]);
+    assert_eq!(sample_curve(&curve, 10.0), curve[0]);
+    assert_eq!(sample_curve(&curve, 20.0), curve[1]);
+    assert_eq!(sample_curve(&curve, 100.0), curve[1]);
+  }
+
+  #[test]
+  fn curve_interpolates_between_keyframes() {
+    let curve = [keyframe(10.0, 2.0), keyframe(20.0, 4.0)];
+    assert_eq!(sample_curve(&curve, 15.0), keyframe(15.0, 3.0));
+  }
+
+  #[test]
+  fn empty_curve_keeps_base_tuning() {
+    assert_eq!(sample_curve(&[], 50.0), DifficultyKeyframe::default());
+  }
+}
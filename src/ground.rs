@@ -1,10 +1,13 @@
 use bevy::prelude::*;
 
 use crate::{
-  collision::Shape,
+  collision::{CollisionLayers, Shape},
+  config::GameConfig,
+  difficulty::Difficulty,
   layer::Layer,
+  physics::Position,
+  settings::Themed,
   state::GameState,
-  GAME_SPEED,
   RESOLUTION,
 };
 
@@ -12,11 +15,19 @@ pub struct GroundPlugin;
 
 impl Plugin for GroundPlugin {
   fn build(&self, app: &mut App) {
-    app.add_systems(Startup, init_ground).add_systems(
-      PostUpdate,
-      animate_ground
-        .run_if(in_state(GameState::Idle).or(in_state(GameState::Going))),
-    );
+    app
+      .add_systems(
+        PreUpdate,
+        respawn_ground.run_if(resource_changed::<GameConfig>),
+      )
+      .add_systems(
+        PostUpdate,
+        animate_ground.run_if(
+          in_state(GameState::MainMenu)
+            .or(in_state(GameState::Idle))
+            .or(in_state(GameState::Going)),
+        ),
+      );
   }
 }
 
@@ -24,36 +35,52 @@ impl Plugin for GroundPlugin {
 pub struct Ground;
 
 impl Ground {
-  pub const LEVEL: f32 = 50.0;
   pub const SEGMENT_WIDTH: f32 = 18.0;
 }
 
-fn init_ground(mut commands: Commands, asset_server: Res<AssetServer>) {
-  let rectangle = Rectangle::new(RESOLUTION.x * 2.0, Ground::LEVEL);
-  commands.spawn((
+fn respawn_ground(
+  mut commands: Commands,
+  asset_server: Option<Res<AssetServer>>,
+  config: Res<GameConfig>,
+  query: Query<Entity, With<Ground>>,
+) {
+  if let Ok(entity) = query.get_single() {
+    commands.entity(entity).despawn();
+  }
+  let rectangle = Rectangle::new(RESOLUTION.x * 2.0, config.ground.level);
+  let spawn_point = Vec2::new(
+    rectangle.half_size.x / 2.0,
+    (-RESOLUTION.y + config.ground.level) / 2.0,
+  );
+  // the ground only scrolls visually, its collider stays in place
+  let mut ground = commands.spawn((
     Ground,
-    Transform::from_xyz(
-      rectangle.half_size.x / 2.0,
-      (-RESOLUTION.y + Ground::LEVEL) / 2.0,
-      Layer::Ground.into(),
-    ),
-    Sprite {
-      image: asset_server.load("base.png"),
-      rect: Some(Rect::from_center_half_size(
-        rectangle.half_size.with_x(RESOLUTION.x),
-        rectangle.half_size.with_x(RESOLUTION.x),
-      )),
-      ..default()
-    },
+    Position(spawn_point),
+    Transform::from_translation(spawn_point.extend(Layer::Ground.into())),
     Shape::Rectangle(rectangle),
+    CollisionLayers::new(CollisionLayers::OBSTACLE, CollisionLayers::BIRD),
   ));
+  if let Some(asset_server) = asset_server {
+    ground.insert((
+      Themed,
+      Sprite {
+        image: asset_server.load("base.png"),
+        rect: Some(Rect::from_center_half_size(
+          rectangle.half_size.with_x(RESOLUTION.x),
+          rectangle.half_size.with_x(RESOLUTION.x),
+        )),
+        ..default()
+      },
+    ));
+  }
 }
 
 fn animate_ground(
   time: Res<Time>,
+  difficulty: Res<Difficulty>,
   mut transform: Single<&mut Transform, With<Ground>>,
 ) {
-  transform.translation.x -= time.delta_secs() * GAME_SPEED;
+  transform.translation.x -= time.delta_secs() * difficulty.game_speed;
   if transform.translation.x <= -RESOLUTION.x / 2.0 {
     transform.translation.x = 0.0 - Ground::SEGMENT_WIDTH;
   }
@@ -0,0 +1,240 @@
+//! An environment to train agents on the game, in the fashion of Gym.
+
+use bevy::{
+  ecs::{event::EventCursor, schedule::ExecutorKind},
+  prelude::*,
+  state::state::StateTransition,
+  time::TimeUpdateStrategy,
+};
+
+use crate::{
+  bird::{
+    Bird,
+    BirdController,
+    BirdCount,
+    BirdDied,
+    DefaultBirdController,
+    FlapInput,
+    Velocity,
+  },
+  config::GameConfig,
+  physics::Position,
+  pipe::{next_gap, Pipe, PipeGap},
+  rng::RunSeed,
+  score::Score,
+  state::GameState,
+  HeadlessPlugin,
+};
+
+/// Runs the game headlessly one fixed tick at a time on behalf of an agent.
+///
+/// ```no_run
+/// use floppy_burp::gym::{Action, Env};
+///
+/// let mut env = Env::new();
+/// let mut observation = env.reset(42);
+/// loop {
+///   let action = if observation.bird_y < 0.0 {
+///     Action::Flap
+///   } else {
+///     Action::Wait
+///   };
+///   let step = env.step(action);
+///   if step.done {
+///     break;
+///   }
+///   observation = step.observation;
+/// }
+/// ```
+pub struct Env {
+  app: App,
+  pub rewards: Rewards,
+  died_cursor: EventCursor<BirdDied>,
+  done: bool,
+}
+
+/// What the agent does on a step.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Action {
+  /// Leaves the flap button up.
+  #[default]
+  Wait,
+  /// Holds the flap button down. The bird flaps when the button gets pressed,
+  /// so it has to wait for a step in between flaps.
+  Flap,
+}
+
+/// What the agent knows about the game after a step.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Observation {
+  pub bird_y: f32,
+  /// Vertical velocity of the bird, positive when it goes up.
+  pub bird_velocity: f32,
+  /// The gap the bird has to get through next, if there are pipes ahead.
+  pub next_gap: Option<GapObservation>,
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct GapObservation {
+  /// Horizontal distance from the bird to the middle of the pipes.
+  pub distance: f32,
+  /// Vertical position of the middle of the gap.
+  pub center: f32,
+  pub height: f32,
+}
+
+/// Outcome of a step.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Step {
+  pub observation: Observation,
+  pub reward: f32,
+  /// Whether the bird died. The run has to be [reset](Env::reset) to go on.
+  pub done: bool,
+}
+
+/// How much the agent gets for what happens on a step.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rewards {
+  /// For every step the bird survives.
+  pub alive: f32,
+  /// For every pipe the bird gets through.
+  pub checkpoint: f32,
+  /// For hitting an obstacle.
+  pub death: f32,
+}
+
+impl Default for Rewards {
+  fn default() -> Self {
+    Self {
+      alive: 0.01,
+      checkpoint: 1.0,
+      death: -1.0,
+    }
+  }
+}
+
+impl Env {
+  /// An environment playing the game with the default tuning.
+  pub fn new() -> Self {
+    Self::with_config(GameConfig::default())
+  }
+
+  pub fn with_config(config: GameConfig) -> Self {
+    let mut app = App::new();
+    app
+      .insert_resource(config)
+      .insert_resource(DefaultBirdController(BirdController::External))
+      .add_plugins(HeadlessPlugin);
+    // every update advances the game by exactly one fixed tick
+    let timestep = app.world().resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+    // a tick is too little work to be worth spreading over threads
+    for (_, schedule) in app.world_mut().resource_mut::<Schedules>().iter_mut()
+    {
+      schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+    }
+    app.finish();
+    app.cleanup();
+    // the first update only starts the clock
+    app.update();
+    let died_cursor = app.world().resource::<Events<BirdDied>>().get_cursor();
+    Self {
+      app,
+      rewards: default(),
+      died_cursor,
+      done: true,
+    }
+  }
+
+  /// Starts a new run, laid out by `seed`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if [`BirdCount`] was changed from 1. The agent plays a single
+  /// bird.
+  pub fn reset(&mut self, seed: u64) -> Observation {
+    let world = self.app.world_mut();
+    let bird_count = world.resource::<BirdCount>().0;
+    assert_eq!(
+      bird_count, 1,
+      "the environment plays a single bird, but BirdCount is {bird_count}",
+    );
+    world.insert_resource(RunSeed::Fixed(seed));
+    // runs only start from states other than dying
+    for state in [GameState::Idle, GameState::Going] {
+      world.resource_mut::<NextState<GameState>>().set(state);
+      world.run_schedule(StateTransition);
+    }
+    self.died_cursor.clear(world.resource::<Events<BirdDied>>());
+    self.done = false;
+    self.observe()
+  }
+
+  /// Advances the run by one fixed tick, during which the agent acts.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the run hasn't been [reset](Env::reset) since it was done.
+  pub fn step(&mut self, action: Action) -> Step {
+    assert!(!self.done, "the run is done, it has to be reset first");
+    let world = self.app.world_mut();
+    let score = **world.resource::<Score>();
+    let mut flap_input = world
+      .query_filtered::<&mut FlapInput, With<Bird>>()
+      .single_mut(world);
+    flap_input.0 = action == Action::Flap;
+    self.app.update();
+    let world = self.app.world();
+    let checkpoints = **world.resource::<Score>() - score;
+    self.done = self
+      .died_cursor
+      .read(world.resource::<Events<BirdDied>>())
+      .next()
+      .is_some();
+    let mut reward = checkpoints as f32 * self.rewards.checkpoint;
+    reward += if self.done {
+      self.rewards.death
+    } else {
+      self.rewards.alive
+    };
+    Step {
+      observation: self.observe(),
+      reward,
+      done: self.done,
+    }
+  }
+
+  /// The app running the game, to inspect or tweak it.
+  pub fn app(&mut self) -> &mut App {
+    &mut self.app
+  }
+
+  fn observe(&mut self) -> Observation {
+    let world = self.app.world_mut();
+    let radius = world.resource::<GameConfig>().bird.hitbox_radius;
+    let (bird, velocity) = world
+      .query_filtered::<(&Position, &Velocity), With<Bird>>()
+      .single(world);
+    let (bird, velocity) = (bird.0, **velocity);
+    let mut pipes = world.query_filtered::<(&Position, &PipeGap), With<Pipe>>();
+    let next_gap =
+      next_gap(pipes.iter(world), bird.x, radius).map(|(x, gap)| {
+        GapObservation {
+          distance: x - bird.x,
+          center: gap.center,
+          height: gap.height,
+        }
+      });
+    Observation {
+      bird_y: bird.y,
+      bird_velocity: velocity,
+      next_gap,
+    }
+  }
+}
+
+impl Default for Env {
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -0,0 +1,198 @@
+use bevy::{input::InputSystem, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+pub struct InputActionPlugin;
+
+impl Plugin for InputActionPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<InputBindings>()
+      .init_resource::<ButtonInput<InputAction>>()
+      .add_systems(PreUpdate, update_actions.after(InputSystem));
+  }
+}
+
+/// Things the player can do, independent of the device used to do them.
+///
+/// Systems read them from [`ButtonInput<InputAction>`] instead of reading
+/// devices directly, so bindings can be changed in [`InputBindings`].
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum InputAction {
+  Flap,
+  /// Flap of the second player in versus mode.
+  Flap2,
+  Pause,
+  Confirm,
+  Back,
+  /// Moves the focus of menus.
+  Up,
+  Down,
+  /// Changes the focused value in menus.
+  Left,
+  Right,
+  Replay,
+  Leaderboard,
+}
+
+/// A physical input that can trigger an [`InputAction`].
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputBinding {
+  Key(KeyCode),
+  Mouse(MouseButton),
+  /// A button on any connected gamepad.
+  Gamepad(GamepadButton),
+  /// A finger anywhere on the screen.
+  Touch,
+}
+
+/// Which inputs trigger which actions. Actions missing from deserialized
+/// bindings keep their default bindings.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(from = "BindingMap", into = "BindingMap")]
+pub struct InputBindings(BindingMap);
+
+type BindingMap = HashMap<InputAction, Vec<InputBinding>>;
+
+impl InputBindings {
+  pub fn get(&self, action: InputAction) -> &[InputBinding] {
+    self.0.get(&action).map_or(&[], Vec::as_slice)
+  }
+
+  /// Replaces the bindings of `action`.
+  pub fn rebind(
+    &mut self,
+    action: InputAction,
+    bindings: impl IntoIterator<Item = InputBinding>,
+  ) {
+    self.0.insert(action, bindings.into_iter().collect());
+  }
+}
+
+impl From<BindingMap> for InputBindings {
+  fn from(map: BindingMap) -> Self {
+    let mut bindings = Self::default();
+    bindings.0.extend(map);
+    bindings
+  }
+}
+
+impl From<InputBindings> for BindingMap {
+  fn from(bindings: InputBindings) -> Self {
+    bindings.0
+  }
+}
+
+impl Default for InputBindings {
+  fn default() -> Self {
+    use InputAction::*;
+    use InputBinding::{Gamepad, Key, Mouse, Touch};
+    Self(HashMap::from([
+      (
+        Flap,
+        vec![
+          Key(KeyCode::Space),
+          Mouse(MouseButton::Left),
+          Gamepad(GamepadButton::South),
+          Touch,
+        ],
+      ),
+      (Flap2, vec![Key(KeyCode::Enter), Key(KeyCode::NumpadEnter)]),
+      (
+        Pause,
+        vec![Key(KeyCode::Escape), Gamepad(GamepadButton::Start)],
+      ),
+      (
+        Confirm,
+        vec![
+          Key(KeyCode::Space),
+          Key(KeyCode::Enter),
+          Gamepad(GamepadButton::South),
+        ],
+      ),
+      (
+        Back,
+        vec![
+          Key(KeyCode::Escape),
+          Key(KeyCode::Backspace),
+          Gamepad(GamepadButton::East),
+        ],
+      ),
+      (
+        Up,
+        vec![
+          Key(KeyCode::ArrowUp),
+          Key(KeyCode::KeyW),
+          Gamepad(GamepadButton::DPadUp),
+        ],
+      ),
+      (
+        Down,
+        vec![
+          Key(KeyCode::ArrowDown),
+          Key(KeyCode::KeyS),
+          Gamepad(GamepadButton::DPadDown),
+        ],
+      ),
+      (
+        Left,
+        vec![
+          Key(KeyCode::ArrowLeft),
+          Key(KeyCode::KeyA),
+          Gamepad(GamepadButton::DPadLeft),
+        ],
+      ),
+      (
+        Right,
+        vec![
+          Key(KeyCode::ArrowRight),
+          Key(KeyCode::KeyD),
+          Gamepad(GamepadButton::DPadRight),
+        ],
+      ),
+      (
+        Replay,
+        vec![Key(KeyCode::KeyR), Gamepad(GamepadButton::West)],
+      ),
+      (
+        Leaderboard,
+        vec![Key(KeyCode::KeyL), Gamepad(GamepadButton::North)],
+      ),
+    ]))
+  }
+}
+
+pub(crate) fn update_actions(
+  bindings: Res<InputBindings>,
+  keys: Res<ButtonInput<KeyCode>>,
+  mouse_buttons: Res<ButtonInput<MouseButton>>,
+  touches: Res<Touches>,
+  gamepads: Query<&Gamepad>,
+  mut actions: ResMut<ButtonInput<InputAction>>,
+) {
+  actions.clear();
+  for (&action, action_bindings) in &bindings.0 {
+    let pressed = action_bindings.iter().any(|binding| match *binding {
+      InputBinding::Key(key) => keys.pressed(key),
+      InputBinding::Mouse(button) => mouse_buttons.pressed(button),
+      InputBinding::Gamepad(button) => {
+        gamepads.iter().any(|gamepad| gamepad.pressed(button))
+      }
+      InputBinding::Touch => touches.iter().next().is_some(),
+    });
+    // inputs pressed and released within one frame still count as presses
+    let tapped = action_bindings.iter().any(|binding| match *binding {
+      InputBinding::Key(key) => keys.just_pressed(key),
+      InputBinding::Mouse(button) => mouse_buttons.just_pressed(button),
+      InputBinding::Gamepad(button) => {
+        gamepads.iter().any(|gamepad| gamepad.just_pressed(button))
+      }
+      InputBinding::Touch => touches.any_just_pressed(),
+    });
+    if pressed || tapped {
+      actions.press(action);
+    }
+    if !pressed {
+      actions.release(action);
+    }
+  }
+}
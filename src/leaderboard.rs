@@ -0,0 +1,134 @@
+use std::{
+  io::ErrorKind,
+  path::PathBuf,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  autopilot::Demo,
+  replay::{finish_playback, Playback},
+  rng::GameRng,
+  score::Score,
+  state::{GameState, RunTicks},
+  storage,
+  versus::Versus,
+};
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<LeaderboardFile>()
+      .init_resource::<Leaderboard>()
+      .add_systems(Startup, load_leaderboard)
+      .add_systems(
+        OnEnter(GameState::GameOver),
+        record_score.before(finish_playback).run_if(
+          not(resource_exists::<Playback>)
+            .and(not(resource_exists::<Demo>))
+            .and(not(resource_exists::<Versus>)),
+        ),
+      );
+  }
+}
+
+/// Best runs, from the highest score to the lowest.
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+pub struct Leaderboard(Vec<LeaderboardEntry>);
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct LeaderboardEntry {
+  pub score: u32,
+  /// Seconds since the Unix epoch when the run was over.
+  pub date: u64,
+  pub seed: u64,
+  pub duration_secs: f32,
+}
+
+impl Leaderboard {
+  pub const CAPACITY: usize = 10;
+
+  pub fn entries(&self) -> &[LeaderboardEntry] {
+    &self.0
+  }
+
+  pub fn best_score(&self) -> Option<u32> {
+    self.0.first().map(|entry| entry.score)
+  }
+
+  /// Puts the entry in its place and returns its rank, if it's good enough to
+  /// make it to the table.
+  pub fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+    // later runs go below earlier ones with the same score
+    let rank = self.0.partition_point(|e| e.score >= entry.score);
+    if rank >= Self::CAPACITY {
+      return None;
+    }
+    self.0.insert(rank, entry);
+    self.0.truncate(Self::CAPACITY);
+    Some(rank)
+  }
+}
+
+impl LeaderboardEntry {
+  /// Date of the run formatted as `YYYY-MM-DD`, in UTC.
+  pub fn date_string(&self) -> String {
+    storage::date_string(self.date)
+  }
+}
+
+/// File the leaderboard is kept in. The leaderboard lives only in memory if
+/// it's not set.
+#[derive(Resource, Default, Debug)]
+pub struct LeaderboardFile(pub Option<PathBuf>);
+
+fn load_leaderboard(
+  file: Res<LeaderboardFile>,
+  mut leaderboard: ResMut<Leaderboard>,
+) {
+  let Some(path) = &file.0 else {
+    return;
+  };
+  match storage::load(path) {
+    Ok(loaded) => *leaderboard = loaded,
+    Err(e) if e.kind() == ErrorKind::NotFound => {}
+    Err(e) => {
+      error!("failed to load leaderboard from {}: {e}", path.display())
+    }
+  }
+}
+
+pub(crate) fn record_score(
+  file: Res<LeaderboardFile>,
+  score: Res<Score>,
+  rng: Res<GameRng>,
+  ticks: Res<RunTicks>,
+  fixed_time: Res<Time<Fixed>>,
+  mut leaderboard: ResMut<Leaderboard>,
+) {
+  if **score == 0 {
+    return;
+  }
+  let entry = LeaderboardEntry {
+    score: **score,
+    date: SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default()
+      .as_secs(),
+    seed: rng.seed(),
+    duration_secs: **ticks as f32 * fixed_time.timestep().as_secs_f32(),
+  };
+  let Some(rank) = leaderboard.insert(entry) else {
+    return;
+  };
+  debug!("score {} is ranked {}", entry.score, rank + 1);
+  if let Some(path) = &file.0 {
+    if let Err(e) = storage::save(path, &*leaderboard) {
+      error!("failed to save leaderboard to {}: {e}", path.display());
+    }
+  }
+}
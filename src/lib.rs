@@ -2,32 +2,57 @@
 
 use bevy::{prelude::*, window::EnabledButtons};
 
+mod audio;
+pub mod autopilot;
 mod background;
-mod bird;
-mod checkpoint;
-mod collision;
-mod ground;
+pub mod bird;
+pub mod checkpoint;
+pub mod collision;
+pub mod config;
+pub mod difficulty;
+pub mod ground;
+pub mod gym;
+pub mod input;
 mod layer;
-mod pipe;
-mod score;
-mod state;
+pub mod leaderboard;
+pub mod physics;
+pub mod pipe;
+pub mod replay;
+pub mod rng;
+pub mod score;
+pub mod settings;
+pub mod state;
+mod storage;
 mod ui;
+pub mod versus;
 
+use audio::AudioPlugin;
+use autopilot::AutopilotPlugin;
 use background::BackgroundPlugin;
 use bevy::{
+  input::InputPlugin,
   log::LogPlugin,
-  window::{PresentMode, WindowResolution},
+  state::app::StatesPlugin,
+  window::WindowResolution,
 };
 use bird::BirdPlugin;
 use checkpoint::CheckpointPlugin;
 use collision::CollisionPlugin;
+use config::ConfigPlugin;
+use difficulty::DifficultyPlugin;
 use ground::GroundPlugin;
+use input::InputActionPlugin;
+use leaderboard::{LeaderboardFile, LeaderboardPlugin};
+use physics::PhysicsPlugin;
 use pipe::PipePlugin;
+use replay::{ReplayDir, ReplayPlugin};
+use rng::RngPlugin;
 use score::ScorePlugin;
+use settings::{Settings, SettingsFile, SettingsPlugin};
 use state::{GameState, GameStatePlugin};
 use ui::UiPlugin;
+use versus::VersusPlugin;
 
-const GAME_SPEED: f32 = 100.0;
 const PHYSICAL_RESOLUTION: Vec2 = Vec2::new(360.0, 720.0);
 const SCALE_FACTOR: f32 = 2.0;
 const RESOLUTION: Vec2 = Vec2::new(
@@ -35,18 +60,30 @@ const RESOLUTION: Vec2 = Vec2::new(
   PHYSICAL_RESOLUTION.y / SCALE_FACTOR,
 );
 
+/// Runs the game in a window.
 pub struct FloppyBurpPlugin;
 
 impl Plugin for FloppyBurpPlugin {
   fn build(&self, app: &mut App) {
+    let settings_file = storage::data_dir().map(|dir| dir.join("settings.ron"));
+    // logging isn't set up yet
+    let settings = settings_file
+      .as_ref()
+      .map_or_else(|| Ok(Settings::default()), Settings::load_or_default);
+    let settings = settings.unwrap_or_else(|e| {
+      eprintln!("failed to load settings, using the defaults: {e}");
+      Settings::default()
+    });
     let default_plugins = DefaultPlugins
       .set(WindowPlugin {
         primary_window: Some(Window {
           title: "Floppy Burp".into(),
           position: WindowPosition::Centered(MonitorSelection::Primary),
-          resolution: WindowResolution::from(PHYSICAL_RESOLUTION)
-            .with_scale_factor_override(SCALE_FACTOR),
-          present_mode: PresentMode::AutoVsync,
+          resolution: WindowResolution::from(
+            RESOLUTION * settings.scale_factor,
+          )
+          .with_scale_factor_override(settings.scale_factor),
+          present_mode: settings.present_mode(),
           resizable: false,
           enabled_buttons: EnabledButtons {
             maximize: false,
@@ -65,18 +102,65 @@ impl Plugin for FloppyBurpPlugin {
     app
       .add_plugins((
         default_plugins,
+        AudioPlugin,
         BackgroundPlugin,
-        GameStatePlugin,
+        GameplayPlugin,
+        SettingsPlugin,
+        UiPlugin,
+      ))
+      .insert_state(GameState::MainMenu)
+      .insert_resource(settings)
+      .insert_resource(SettingsFile(settings_file))
+      .insert_resource(ReplayDir(
+        storage::data_dir().map(|dir| dir.join("replays")),
+      ))
+      .insert_resource(LeaderboardFile(
+        storage::data_dir().map(|dir| dir.join("leaderboard.ron")),
+      ))
+      .add_systems(Startup, setup_game);
+  }
+}
+
+/// Runs the game simulation without a window, a GPU or loaded assets.
+///
+/// The app is meant to be driven manually with [`App::update`], pressing keys
+/// through [`ButtonInput<KeyCode>`] and inspecting the world in between.
+/// Insert [`rng::RunSeed`] before adding it to make runs reproducible. It
+/// skips the main menu and starts in [`GameState::Idle`]. Without an
+/// [`AssetServer`], the game spawns its entities without sprites.
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_plugins((MinimalPlugins, StatesPlugin, InputPlugin, GameplayPlugin))
+      .insert_state(GameState::Idle);
+  }
+}
+
+/// Plugins that make up the game itself, independent of how it is presented.
+struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_plugins((
+        ConfigPlugin,
+        InputActionPlugin,
         CollisionPlugin,
+        GameStatePlugin,
+        RngPlugin,
+        PhysicsPlugin,
+        DifficultyPlugin,
         GroundPlugin,
         BirdPlugin,
         PipePlugin,
         CheckpointPlugin,
         ScorePlugin,
-        UiPlugin,
+        LeaderboardPlugin,
       ))
-      .insert_state(GameState::Idle)
-      .add_systems(Startup, setup_game);
+      // other ways to play than a player on their own
+      .add_plugins((AutopilotPlugin, ReplayPlugin, VersusPlugin));
   }
 }
 
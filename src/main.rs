@@ -1,6 +1,24 @@
-use bevy::prelude::App;
-use floppy_burp::FloppyBurpPlugin;
+use std::env;
+
+use bevy::{log::error, prelude::App};
+use floppy_burp::{
+  replay::{Replay, StartReplay},
+  FloppyBurpPlugin,
+};
 
 fn main() {
-  App::new().add_plugins(FloppyBurpPlugin).run();
+  let mut app = App::new();
+  app.add_plugins(FloppyBurpPlugin);
+  // a replay file passed as an argument is played back right away
+  if let Some(path) = env::args_os().nth(1) {
+    match Replay::load(&path) {
+      Ok(replay) => {
+        app.world_mut().send_event(StartReplay(replay));
+      }
+      Err(e) => {
+        error!("failed to load replay {}: {e}", path.to_string_lossy())
+      }
+    }
+  }
+  app.run();
 }
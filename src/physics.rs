@@ -0,0 +1,64 @@
+use bevy::{app::RunFixedMainLoopSystem, prelude::*};
+
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .configure_sets(
+        FixedUpdate,
+        (PhysicsSet::Movement, PhysicsSet::Collision).chain(),
+      )
+      .add_systems(FixedFirst, save_previous_positions)
+      .add_systems(
+        RunFixedMainLoop,
+        interpolate_transforms
+          .in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+      );
+  }
+}
+
+/// Stages of a fixed tick of the simulation.
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PhysicsSet {
+  /// Bodies change their [`Position`].
+  Movement,
+  /// Bodies are tested against each other at their new positions.
+  Collision,
+}
+
+/// Position of a body in the simulation. It only changes on the fixed
+/// timestep, so gameplay doesn't depend on the frame rate.
+#[derive(Component, Deref, DerefMut, Clone, Copy, Default, Debug)]
+pub struct Position(pub Vec2);
+
+/// [`Position`] of a body at the previous fixed tick. Bodies that have it get
+/// their [`Transform`] interpolated between the two for rendering.
+#[derive(Component, Deref, DerefMut, Clone, Copy, Default, Debug)]
+pub struct PreviousPosition(pub Vec2);
+
+impl Position {
+  /// Position of a body that is rendered in between fixed ticks.
+  pub fn interpolated(position: Vec2) -> (Position, PreviousPosition) {
+    (Position(position), PreviousPosition(position))
+  }
+}
+
+fn save_previous_positions(
+  mut query: Query<(&Position, &mut PreviousPosition)>,
+) {
+  for (position, mut previous_position) in &mut query {
+    previous_position.0 = position.0;
+  }
+}
+
+fn interpolate_transforms(
+  time: Res<Time<Fixed>>,
+  mut query: Query<(&mut Transform, &Position, &PreviousPosition)>,
+) {
+  let overstep = time.overstep_fraction();
+  for (mut transform, position, previous_position) in &mut query {
+    let translation = previous_position.lerp(position.0, overstep);
+    transform.translation = translation.extend(transform.translation.z);
+  }
+}
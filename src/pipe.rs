@@ -1,13 +1,19 @@
+use std::{f32::consts::TAU, time::Duration};
+
 use bevy::prelude::*;
-use rand::Rng;
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::{Deserialize, Serialize};
 
 use crate::{
+  bird::{Bird, Dead},
   checkpoint::Checkpoint,
-  collision::Shape,
-  ground::Ground,
+  collision::{CollisionLayers, Shape},
+  config::GameConfig,
+  difficulty::{reset_difficulty, update_difficulty, Difficulty},
   layer::Layer,
-  state::GameState,
-  GAME_SPEED,
+  physics::{PhysicsSet, Position},
+  rng::{reseed_rng, GameRng},
+  state::{GameState, OnRunStart},
   RESOLUTION,
 };
 
@@ -15,22 +21,33 @@ pub struct PipePlugin;
 
 impl Plugin for PipePlugin {
   fn build(&self, app: &mut App) {
-    let pipe_reset_system_set = (
-      reset_spawn_timer, //
-      despawn_all_pipes,
-      spawn_pipes_randomly,
-    );
     app
-      .insert_resource(PipeSpawnTimer::from_seconds(Pipe::RESPAWN_COOLDOWN_SEC))
-      .add_systems(OnExit(GameState::Idle), pipe_reset_system_set)
-      .add_systems(OnExit(GameState::GameOver), pipe_reset_system_set)
+      .init_resource::<PipeSpawnTimer>()
+      // runs can be abandoned from the pause menu
+      .add_systems(OnEnter(GameState::Idle), despawn_all_pipes)
+      .add_systems(OnEnter(GameState::MainMenu), despawn_all_pipes)
+      .add_systems(
+        OnRunStart,
+        (
+          reset_spawn_timer, //
+          despawn_all_pipes,
+          spawn_pipes_randomly
+            .after(reseed_rng)
+            .after(reset_difficulty),
+        ),
+      )
       .add_systems(
-        Update,
+        FixedUpdate,
         (
-          tick_spawn_timer,
-          spawn_pipes_periodically,
-          update_pipes,
-          despawn_out_of_bounds_pipes,
+          (
+            apply_spawn_cooldown,
+            tick_spawn_timer,
+            spawn_pipes_periodically,
+          )
+            .chain()
+            .after(update_difficulty),
+          update_pipes.in_set(PhysicsSet::Movement),
+          despawn_out_of_bounds_pipes.after(PhysicsSet::Movement),
         )
           .run_if(in_state(GameState::Going)),
       );
@@ -41,10 +58,131 @@ impl Plugin for PipePlugin {
 pub struct Pipe;
 
 impl Pipe {
-  pub const HITBOX_SIZE: Vec2 = Vec2 { x: 48.0, y: 318.0 };
-  pub const VERTICAL_GAP: f32 = 80.0;
-  pub const SPAWN_POINT_MID_DISTANCE: f32 = 115.0;
-  pub const RESPAWN_COOLDOWN_SEC: f32 = 2.0;
+  /// Size of the box around the hitbox of a pipe, lip included.
+  pub const HITBOX_SIZE: Vec2 = Vec2 { x: 52.0, y: 318.0 };
+  /// Size of the wide end of a pipe that faces the gap.
+  const LIP_SIZE: Vec2 = Vec2 { x: 52.0, y: 23.0 };
+  const BODY_WIDTH: f32 = 48.0;
+
+  /// A narrow body with a lip at the end that faces the gap.
+  fn shape(side: PipeSide) -> Shape {
+    let body_size =
+      Vec2::new(Self::BODY_WIDTH, Self::HITBOX_SIZE.y - Self::LIP_SIZE.y);
+    let toward_gap = -side.sign();
+    let body_y = -toward_gap * Self::LIP_SIZE.y / 2.0;
+    let lip_y = toward_gap * (Self::HITBOX_SIZE.y - Self::LIP_SIZE.y) / 2.0;
+    Shape::Compound(vec![
+      (
+        Isometry2d::from_xy(0.0, body_y),
+        Shape::Rectangle(Rectangle::from_size(body_size)),
+      ),
+      (
+        Isometry2d::from_xy(0.0, lip_y),
+        Shape::Rectangle(Rectangle::from_size(Self::LIP_SIZE)),
+      ),
+    ])
+  }
+}
+
+/// How a pair of pipes moves besides scrolling to the left. Both pipes of a
+/// pair carry the same pattern.
+#[derive(
+  Component, Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default,
+)]
+pub enum PipePattern {
+  #[default]
+  Static,
+  /// The gap moves up and down.
+  Oscillating { amplitude: f32, period_secs: f32 },
+  /// The gap opens and closes.
+  Breathing { amplitude: f32, period_secs: f32 },
+  /// The gap drifts toward the average height of the birds, in pixels per
+  /// second.
+  Homing { speed: f32 },
+}
+
+impl PipePattern {
+  fn update(&self, gap: &mut PipeGap, bird_y: Option<f32>, delta_secs: f32) {
+    let wave = |period_secs: f32| (TAU * gap.age_secs / period_secs).sin();
+    match *self {
+      Self::Static => {}
+      Self::Oscillating {
+        amplitude,
+        period_secs,
+      } => gap.center = gap.origin + amplitude * wave(period_secs),
+      Self::Breathing {
+        amplitude,
+        period_secs,
+      } => gap.height = gap.base_height + amplitude * wave(period_secs),
+      Self::Homing { speed } => {
+        let Some(bird_y) = bird_y else {
+          return;
+        };
+        let step = speed * delta_secs;
+        gap.center += (bird_y - gap.center).clamp(-step, step);
+      }
+    }
+  }
+}
+
+/// Which side of the gap a pipe is on.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PipeSide {
+  Top,
+  Bottom,
+}
+
+impl PipeSide {
+  fn sign(self) -> f32 {
+    match self {
+      Self::Top => 1.0,
+      Self::Bottom => -1.0,
+    }
+  }
+}
+
+/// The gap between a pair of pipes. Both pipes of a pair keep their own copy
+/// and update it the same way.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PipeGap {
+  /// Vertical position of the middle of the gap.
+  pub center: f32,
+  pub height: f32,
+  origin: f32,
+  base_height: f32,
+  age_secs: f32,
+}
+
+impl PipeGap {
+  fn new(center: f32, height: f32) -> Self {
+    Self {
+      center,
+      height,
+      origin: center,
+      base_height: height,
+      age_secs: 0.0,
+    }
+  }
+
+  fn pipe_y(&self, side: PipeSide) -> f32 {
+    self.center + side.sign() * (self.height + Pipe::HITBOX_SIZE.y) / 2.0
+  }
+}
+
+/// Horizontal position of the pipes and the gap between them that a bird at
+/// `bird_x` hasn't fully passed yet.
+pub(crate) fn next_gap<'a>(
+  pipes: impl IntoIterator<Item = (&'a Position, &'a PipeGap)>,
+  bird_x: f32,
+  bird_radius: f32,
+) -> Option<(f32, PipeGap)> {
+  pipes
+    .into_iter()
+    .filter(|(pipe, _)| {
+      pipe.x + Pipe::HITBOX_SIZE.x / 2.0 + bird_radius >= bird_x
+    })
+    .min_by(|(a, _), (b, _)| a.x.total_cmp(&b.x))
+    .map(|(pipe, gap)| (pipe.x, *gap))
 }
 
 #[derive(Resource, Deref, DerefMut)]
@@ -56,6 +194,27 @@ impl PipeSpawnTimer {
   }
 }
 
+impl FromWorld for PipeSpawnTimer {
+  fn from_world(world: &mut World) -> Self {
+    let config = world.resource::<GameConfig>();
+    Self::from_seconds(config.pipe.respawn_cooldown_secs)
+  }
+}
+
+fn apply_spawn_cooldown(
+  difficulty: Res<Difficulty>,
+  mut spawn_timer: ResMut<PipeSpawnTimer>,
+) {
+  let Ok(duration) =
+    Duration::try_from_secs_f32(difficulty.respawn_cooldown_secs)
+  else {
+    return;
+  };
+  if spawn_timer.duration() != duration {
+    spawn_timer.set_duration(duration);
+  }
+}
+
 fn reset_spawn_timer(mut spawn_timer: ResMut<PipeSpawnTimer>) {
   spawn_timer.reset();
 }
@@ -66,66 +225,106 @@ fn tick_spawn_timer(time: Res<Time>, mut spawn_timer: ResMut<PipeSpawnTimer>) {
 
 fn spawn_pipes_randomly(
   mut commands: Commands,
-  asset_server: Res<AssetServer>,
+  asset_server: Option<Res<AssetServer>>,
+  config: Res<GameConfig>,
+  difficulty: Res<Difficulty>,
+  mut rng: ResMut<GameRng>,
 ) {
-  let image = asset_server.load::<Image>("pipe-green.png");
-  let shape = Shape::Rectangle(Rectangle::from_size(Pipe::HITBOX_SIZE));
-  let spawn_point = Vec2 {
-    x: RESOLUTION.x / 2.0 + Pipe::HITBOX_SIZE.x,
-    y: rand::thread_rng().gen_range(
-      (-Pipe::SPAWN_POINT_MID_DISTANCE + Ground::LEVEL)
-        ..Pipe::SPAWN_POINT_MID_DISTANCE,
+  let x = RESOLUTION.x / 2.0 + Pipe::HITBOX_SIZE.x;
+  let gap = PipeGap::new(
+    rng.gen_range(
+      (-difficulty.spawn_point_mid_distance + config.ground.level)
+        ..difficulty.spawn_point_mid_distance,
     ),
+    difficulty.vertical_gap,
+  );
+  let pattern = pick_pattern(&config, &mut rng);
+  let pipe_bundle = |side| {
+    let position = Vec2::new(x, gap.pipe_y(side));
+    (
+      Pipe,
+      pattern,
+      side,
+      gap,
+      Position::interpolated(position),
+      Transform::from_translation(position.extend(Layer::Pipe.into())),
+      Pipe::shape(side),
+      CollisionLayers::new(CollisionLayers::OBSTACLE, CollisionLayers::BIRD),
+    )
   };
-  commands.spawn((
-    Pipe,
-    Sprite {
+  let mut top_pipe = commands.spawn(pipe_bundle(PipeSide::Top));
+  let image = asset_server.map(|s| s.load::<Image>("pipe-green.png"));
+  if let Some(image) = &image {
+    top_pipe.insert(Sprite {
       image: image.clone(),
       flip_y: true,
       ..default()
-    },
-    Transform::from_xyz(
-      spawn_point.x,
-      spawn_point.y + (Pipe::VERTICAL_GAP + Pipe::HITBOX_SIZE.y) / 2.0,
-      Layer::Pipe.into(),
-    ),
-    shape,
-  ));
-  commands.spawn((
-    Pipe,
-    Sprite::from(image),
-    Transform::from_xyz(
-      spawn_point.x,
-      spawn_point.y - (Pipe::VERTICAL_GAP + Pipe::HITBOX_SIZE.y) / 2.0,
-      Layer::Pipe.into(),
-    ),
-    shape,
-    Checkpoint,
-  ));
+    });
+  }
+  let mut bottom_pipe = commands.spawn(pipe_bundle(PipeSide::Bottom));
+  bottom_pipe.insert(Checkpoint);
+  if let Some(image) = image {
+    bottom_pipe.insert(Sprite::from(image));
+  }
+}
+
+fn pick_pattern(config: &GameConfig, rng: &mut GameRng) -> PipePattern {
+  let patterns = &config.pipe.patterns;
+  match WeightedIndex::new(patterns.iter().map(|p| p.weight)) {
+    Ok(index) => patterns[index.sample(&mut **rng)].pattern,
+    Err(_) => PipePattern::Static,
+  }
 }
 
 fn spawn_pipes_periodically(
   commands: Commands,
-  asset_server: Res<AssetServer>,
+  asset_server: Option<Res<AssetServer>>,
+  config: Res<GameConfig>,
+  difficulty: Res<Difficulty>,
+  rng: ResMut<GameRng>,
   spawn_timer: Res<PipeSpawnTimer>,
 ) {
   if spawn_timer.finished() {
-    spawn_pipes_randomly(commands, asset_server);
+    spawn_pipes_randomly(commands, asset_server, config, difficulty, rng);
   }
 }
 
-fn update_pipes(time: Res<Time>, mut query: Query<&mut Transform, With<Pipe>>) {
-  for mut transform in &mut query {
-    transform.translation.x -= GAME_SPEED * time.delta_secs();
+fn update_pipes(
+  time: Res<Time>,
+  config: Res<GameConfig>,
+  difficulty: Res<Difficulty>,
+  birds: Query<&Position, (With<Bird>, Without<Dead>, Without<Pipe>)>,
+  mut query: Query<
+    (&mut Position, &mut PipeGap, &PipePattern, &PipeSide),
+    With<Pipe>,
+  >,
+) {
+  let delta_secs = time.delta_secs();
+  let bird_count = birds.iter().len();
+  let bird_y = (bird_count > 0)
+    .then(|| birds.iter().map(|bird| bird.y).sum::<f32>() / bird_count as f32);
+  for (mut position, mut gap, pattern, side) in &mut query {
+    position.x -= difficulty.game_speed * delta_secs;
+    gap.age_secs += delta_secs;
+    pattern.update(&mut gap, bird_y, delta_secs);
+    // keeps moving gaps on screen and above the ground, even if they grow
+    // taller than the room there is or breathe shut
+    let floor =
+      -RESOLUTION.y / 2.0 + config.ground.level.clamp(0.0, RESOLUTION.y);
+    let ceiling = RESOLUTION.y / 2.0;
+    gap.height = gap.height.clamp(0.0, ceiling - floor);
+    let half_height = gap.height / 2.0;
+    gap.center = gap.center.clamp(floor + half_height, ceiling - half_height);
+    position.y = gap.pipe_y(*side);
   }
 }
 
 fn despawn_out_of_bounds_pipes(
   mut commands: Commands,
-  query: Query<(Entity, &Transform), With<Pipe>>,
+  query: Query<(Entity, &Position), With<Pipe>>,
 ) {
-  for (pipe, transform) in &query {
-    if transform.translation.x <= -RESOLUTION.x / 2.0 - Pipe::HITBOX_SIZE.x {
+  for (pipe, position) in &query {
+    if position.x <= -RESOLUTION.x / 2.0 - Pipe::HITBOX_SIZE.x {
       commands.entity(pipe).despawn();
     }
   }
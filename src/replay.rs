@@ -0,0 +1,260 @@
+use std::{
+  cmp::Reverse,
+  fs,
+  io::{self, ErrorKind},
+  path::{Path, PathBuf},
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  autopilot::Demo,
+  bird::{Bird, BirdCount, BirdInputSet, FlapInput},
+  input::InputAction,
+  rng::{reseed_rng, GameRng, RunSeed},
+  state::{GameState, OnRunStart, RunTicks},
+  storage,
+};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<ReplayDir>()
+      .init_resource::<Recording>()
+      .add_event::<StartReplay>()
+      .add_systems(OnRunStart, start_recording.after(reseed_rng))
+      .add_systems(
+        OnEnter(GameState::GameOver),
+        (
+          // recordings hold a single bird's flaps
+          save_recording.run_if(
+            not(resource_exists::<Playback>)
+              .and(not(resource_exists::<Demo>))
+              .and(single_bird),
+          ),
+          finish_playback.run_if(resource_exists::<Playback>),
+        )
+          .chain(),
+      )
+      .add_systems(
+        OnEnter(GameState::Idle),
+        finish_playback.run_if(resource_exists::<Playback>),
+      )
+      .add_systems(
+        OnEnter(GameState::MainMenu),
+        finish_playback.run_if(resource_exists::<Playback>),
+      )
+      .add_systems(
+        Update,
+        (
+          replay_last_run
+            .run_if(in_state(GameState::GameOver).and(single_bird)),
+          start_replay,
+        )
+          .chain(),
+      )
+      .add_systems(
+        FixedUpdate,
+        (
+          play_flaps
+            .in_set(BirdInputSet)
+            .run_if(resource_exists::<Playback>),
+          record_flaps.after(BirdInputSet),
+        )
+          .run_if(in_state(GameState::Going)),
+      );
+  }
+}
+
+/// Everything needed to reproduce a run.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Replay {
+  /// Version of the game that recorded the run. Other versions, as well as
+  /// a different game config, may play it back differently.
+  pub version: String,
+  pub seed: u64,
+  /// Fixed ticks of the run on which the flap button was held, in ascending
+  /// order. All birds are played back with the same flaps.
+  pub flaps: Vec<u32>,
+}
+
+impl Replay {
+  pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+  pub fn new(seed: u64) -> Self {
+    Self {
+      version: Self::VERSION.into(),
+      seed,
+      flaps: Vec::new(),
+    }
+  }
+
+  pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+    storage::load(path)
+  }
+
+  pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    storage::save(path, self)
+  }
+
+  fn flaps_on(&self, tick: u32) -> bool {
+    self.flaps.binary_search(&tick).is_ok()
+  }
+}
+
+/// Directory that finished runs are saved to. Runs aren't saved if it's not
+/// set.
+#[derive(Resource, Default, Debug)]
+pub struct ReplayDir(pub Option<PathBuf>);
+
+impl ReplayDir {
+  /// Replays saved to the directory, newest first.
+  pub fn saved_replays(&self) -> io::Result<Vec<PathBuf>> {
+    let Some(dir) = &self.0 else {
+      return Ok(Vec::new());
+    };
+    let entries = match fs::read_dir(dir) {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+      Err(e) => return Err(e),
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+      let path = entry?.path();
+      if path.extension().is_some_and(|ext| ext == "ron") {
+        paths.push(path);
+      }
+    }
+    // replays are named after the time they were saved at
+    paths.sort_by_key(|path| Reverse(Self::saved_at(path)));
+    Ok(paths)
+  }
+
+  /// Unix time a replay was saved at, judging by its file name.
+  pub fn saved_at(path: &Path) -> Option<u64> {
+    let stem = path.file_stem()?.to_str()?;
+    // replays saved within the same second are told apart by their seed
+    let (secs, _seed) = stem.split_once('-').unwrap_or((stem, ""));
+    secs.parse().ok()
+  }
+}
+
+/// Replay of the current or the last finished run.
+#[derive(Resource, Deref, Debug)]
+pub struct Recording(Replay);
+
+impl Default for Recording {
+  fn default() -> Self {
+    Self(Replay::new(0))
+  }
+}
+
+/// Replay that drives the bird instead of the player. It exists from the
+/// moment a replay starts until its run is over.
+#[derive(Resource, Debug)]
+pub struct Playback {
+  replay: Replay,
+  player_run_seed: RunSeed,
+}
+
+/// Starts playing a replay back. Only has effect between runs.
+#[derive(Event, Debug)]
+pub struct StartReplay(pub Replay);
+
+fn single_bird(bird_count: Res<BirdCount>) -> bool {
+  bird_count.0 == 1
+}
+
+fn start_recording(rng: Res<GameRng>, mut recording: ResMut<Recording>) {
+  recording.0 = Replay::new(rng.seed());
+}
+
+fn record_flaps(
+  ticks: Res<RunTicks>,
+  flap_inputs: Query<&FlapInput, With<Bird>>,
+  mut recording: ResMut<Recording>,
+) {
+  if flap_inputs.iter().any(|flap_input| flap_input.0) {
+    recording.0.flaps.push(**ticks);
+  }
+}
+
+fn save_recording(replay_dir: Res<ReplayDir>, recording: Res<Recording>) {
+  let Some(dir) = &replay_dir.0 else {
+    return;
+  };
+  let timestamp = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_secs();
+  let path = dir.join(format!("{timestamp}-{}.ron", recording.seed));
+  match recording.save(&path) {
+    Ok(()) => info!("replay saved to {}", path.display()),
+    Err(e) => error!("failed to save replay to {}: {e}", path.display()),
+  }
+}
+
+fn replay_last_run(
+  actions: Res<ButtonInput<InputAction>>,
+  recording: Res<Recording>,
+  mut events: EventWriter<StartReplay>,
+) {
+  if actions.just_pressed(InputAction::Replay) {
+    events.send(StartReplay(recording.0.clone()));
+  }
+}
+
+fn start_replay(
+  mut commands: Commands,
+  mut events: EventReader<StartReplay>,
+  state: Res<State<GameState>>,
+  mut next_state: ResMut<NextState<GameState>>,
+  mut run_seed: ResMut<RunSeed>,
+) {
+  let Some(StartReplay(replay)) = events.read().last() else {
+    return;
+  };
+  if !matches!(
+    state.get(),
+    GameState::MainMenu | GameState::Idle | GameState::GameOver
+  ) {
+    warn!("replay can't start in the middle of a run");
+    return;
+  }
+  if replay.version != Replay::VERSION {
+    warn!(
+      "replay was recorded by version {}, it may play back differently",
+      replay.version
+    );
+  }
+  commands.insert_resource(Playback {
+    replay: replay.clone(),
+    player_run_seed: *run_seed,
+  });
+  *run_seed = RunSeed::Fixed(replay.seed);
+  next_state.set(GameState::Going);
+}
+
+fn play_flaps(
+  ticks: Res<RunTicks>,
+  playback: Res<Playback>,
+  mut flap_inputs: Query<&mut FlapInput, With<Bird>>,
+) {
+  let flaps = playback.replay.flaps_on(**ticks);
+  for mut flap_input in &mut flap_inputs {
+    flap_input.0 = flaps;
+  }
+}
+
+pub(crate) fn finish_playback(
+  mut commands: Commands,
+  playback: Res<Playback>,
+  mut run_seed: ResMut<RunSeed>,
+) {
+  *run_seed = playback.player_run_seed;
+  commands.remove_resource::<Playback>();
+}
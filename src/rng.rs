@@ -0,0 +1,71 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::state::OnRunStart;
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<RunSeed>()
+      .init_resource::<GameRng>()
+      .add_systems(OnRunStart, reseed_rng);
+  }
+}
+
+/// Decides the seed of every new run. Insert it before the game plugins to
+/// configure it at startup, or change it between runs.
+#[derive(Resource, Clone, Copy, Default, Debug)]
+pub enum RunSeed {
+  /// Every run picks a fresh random seed.
+  #[default]
+  Random,
+  /// Every run starts from the same seed.
+  Fixed(u64),
+}
+
+impl RunSeed {
+  fn pick(self) -> u64 {
+    match self {
+      RunSeed::Random => rand::random(),
+      RunSeed::Fixed(seed) => seed,
+    }
+  }
+}
+
+/// Source of all gameplay randomness. It is reseeded when a run starts, so
+/// the same seed always produces the same level, on every platform and
+/// version of `rand`.
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng {
+  seed: u64,
+  #[deref]
+  rng: ChaCha8Rng,
+}
+
+impl GameRng {
+  pub fn from_seed(seed: u64) -> Self {
+    Self {
+      seed,
+      rng: ChaCha8Rng::seed_from_u64(seed),
+    }
+  }
+
+  /// Seed of the current run.
+  pub fn seed(&self) -> u64 {
+    self.seed
+  }
+}
+
+impl FromWorld for GameRng {
+  fn from_world(world: &mut World) -> Self {
+    Self::from_seed(world.resource::<RunSeed>().pick())
+  }
+}
+
+pub(crate) fn reseed_rng(run_seed: Res<RunSeed>, mut rng: ResMut<GameRng>) {
+  *rng = GameRng::from_seed(run_seed.pick());
+  debug!("run seed is {}", rng.seed());
+}
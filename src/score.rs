@@ -2,7 +2,11 @@ use std::fmt::Display;
 
 use bevy::prelude::*;
 
-use crate::{checkpoint::CheckpointPassed, state::GameState};
+use crate::{
+  checkpoint::CheckpointPassed,
+  physics::PhysicsSet,
+  state::OnRunStart,
+};
 
 pub struct ScorePlugin;
 
@@ -10,29 +14,94 @@ impl Plugin for ScorePlugin {
   fn build(&self, app: &mut App) {
     app
       .init_resource::<Score>()
-      .add_systems(OnExit(GameState::Idle), reset_score)
-      .add_systems(OnExit(GameState::GameOver), reset_score)
-      .add_systems(FixedUpdate, increment_score);
+      .init_resource::<MedalThresholds>()
+      .add_systems(OnRunStart, reset_score)
+      .add_systems(FixedUpdate, increment_score.after(PhysicsSet::Collision));
   }
 }
 
+/// Pipes passed in the current run, by the bird that passed the most.
 #[derive(Resource, Deref, Default)]
 pub struct Score(u32);
 
+/// Pipes a bird passed in the current run.
+#[derive(Component, Deref, Clone, Copy, Default, Debug)]
+pub struct BirdScore(u32);
+
 impl Display for Score {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     write!(f, "{}", self.0)
   }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Medal {
+  Bronze,
+  Silver,
+  Gold,
+  Platinum,
+}
+
+impl Display for Medal {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let name = match self {
+      Medal::Bronze => "BRONZE",
+      Medal::Silver => "SILVER",
+      Medal::Gold => "GOLD",
+      Medal::Platinum => "PLATINUM",
+    };
+    write!(f, "{name}")
+  }
+}
+
+/// Lowest scores that earn each medal.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct MedalThresholds {
+  pub bronze: u32,
+  pub silver: u32,
+  pub gold: u32,
+  pub platinum: u32,
+}
+
+impl Default for MedalThresholds {
+  fn default() -> Self {
+    Self {
+      bronze: 10,
+      silver: 20,
+      gold: 30,
+      platinum: 40,
+    }
+  }
+}
+
+impl MedalThresholds {
+  pub fn medal(&self, score: u32) -> Option<Medal> {
+    [
+      (self.platinum, Medal::Platinum),
+      (self.gold, Medal::Gold),
+      (self.silver, Medal::Silver),
+      (self.bronze, Medal::Bronze),
+    ]
+    .into_iter()
+    .find(|(threshold, _)| score >= *threshold)
+    .map(|(_, medal)| medal)
+  }
+}
+
 fn increment_score(
   mut score: ResMut<Score>,
   mut checkpoint_events: EventReader<CheckpointPassed>,
+  mut bird_scores: Query<&mut BirdScore>,
 ) {
-  let score_delta = checkpoint_events.read().count() as u32;
-  if score_delta > 0 {
-    score.0 = score.0.saturating_add(score_delta);
-    debug!("score is {}", score.0);
+  for event in checkpoint_events.read() {
+    let Ok(mut bird_score) = bird_scores.get_mut(event.bird) else {
+      continue;
+    };
+    bird_score.0 = bird_score.0.saturating_add(1);
+    if bird_score.0 > score.0 {
+      score.0 = bird_score.0;
+      debug!("score is {}", score.0);
+    }
   }
 }
 
@@ -0,0 +1,170 @@
+use std::{
+  io::{self, ErrorKind},
+  path::{Path, PathBuf},
+};
+
+use bevy::{
+  prelude::*,
+  window::{PresentMode, PrimaryWindow},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{input::InputBindings, storage, RESOLUTION, SCALE_FACTOR};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<Settings>()
+      .init_resource::<SettingsFile>()
+      .add_systems(
+        PreUpdate,
+        (
+          apply_settings,
+          save_settings.run_if(not(resource_added::<Settings>)),
+        )
+          .run_if(resource_changed::<Settings>),
+      )
+      .add_systems(PostUpdate, tint_themed_sprites);
+  }
+}
+
+/// Preferences of the player, kept between launches.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+  /// Volume of all sounds, from 0 to 1.
+  pub master_volume: f32,
+  pub effects_volume: f32,
+  pub music_volume: f32,
+  /// How many physical pixels a pixel of the game takes on each side.
+  pub scale_factor: f32,
+  pub vsync: bool,
+  pub theme: Theme,
+  pub bindings: InputBindings,
+}
+
+impl Settings {
+  pub const MIN_SCALE_FACTOR: f32 = 1.0;
+  pub const MAX_SCALE_FACTOR: f32 = 4.0;
+
+  /// Loads the settings, bringing the scale factor into the range the
+  /// settings screen offers.
+  pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+    let mut settings: Self = storage::load(path)?;
+    settings.scale_factor = if settings.scale_factor.is_finite() {
+      settings
+        .scale_factor
+        .round()
+        .clamp(Self::MIN_SCALE_FACTOR, Self::MAX_SCALE_FACTOR)
+    } else {
+      Self::default().scale_factor
+    };
+    Ok(settings)
+  }
+
+  /// Loads the settings, falling back to the defaults if there are none yet.
+  pub fn load_or_default(path: impl AsRef<Path>) -> io::Result<Self> {
+    match Self::load(path) {
+      Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+      result => result,
+    }
+  }
+
+  pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    storage::save(path, self)
+  }
+
+  pub fn present_mode(&self) -> PresentMode {
+    if self.vsync {
+      PresentMode::AutoVsync
+    } else {
+      PresentMode::AutoNoVsync
+    }
+  }
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Self {
+      master_volume: 1.0,
+      effects_volume: 1.0,
+      music_volume: 0.6,
+      scale_factor: SCALE_FACTOR,
+      vsync: true,
+      theme: default(),
+      bindings: default(),
+    }
+  }
+}
+
+/// Colour theme of the scenery.
+#[derive(
+  Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default,
+)]
+pub enum Theme {
+  #[default]
+  Day,
+  Dusk,
+  Night,
+}
+
+impl Theme {
+  pub const ALL: [Self; 3] = [Self::Day, Self::Dusk, Self::Night];
+
+  /// Colour that sprites of the scenery are multiplied by.
+  pub fn tint(self) -> Color {
+    match self {
+      Self::Day => Color::WHITE,
+      Self::Dusk => Color::srgb(1.0, 0.75, 0.6),
+      Self::Night => Color::srgb(0.45, 0.5, 0.8),
+    }
+  }
+}
+
+/// Marks sprites that take the colour of the [`Theme`].
+#[derive(Component, Default)]
+pub struct Themed;
+
+/// File the settings are kept in. The settings aren't saved if it's not set.
+#[derive(Resource, Default, Debug)]
+pub struct SettingsFile(pub Option<PathBuf>);
+
+fn apply_settings(
+  settings: Res<Settings>,
+  mut bindings: ResMut<InputBindings>,
+  mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+  bindings.set_if_neq(settings.bindings.clone());
+  for mut window in &mut windows {
+    let scale_factor = Some(settings.scale_factor);
+    if window.resolution.scale_factor_override() != scale_factor {
+      window.resolution.set_scale_factor_override(scale_factor);
+      // the window grows or shrinks to keep showing the whole game
+      window.resolution.set(RESOLUTION.x, RESOLUTION.y);
+    }
+    window.present_mode = settings.present_mode();
+  }
+}
+
+fn save_settings(file: Res<SettingsFile>, settings: Res<Settings>) {
+  let Some(path) = &file.0 else {
+    return;
+  };
+  if let Err(e) = settings.save(path) {
+    error!("failed to save settings to {}: {e}", path.display());
+  }
+}
+
+fn tint_themed_sprites(
+  settings: Res<Settings>,
+  mut query: Query<(&mut Sprite, Ref<Themed>)>,
+) {
+  let tint = settings.theme.tint();
+  for (mut sprite, themed) in &mut query {
+    if settings.is_changed() || themed.is_added() {
+      sprite.color = tint;
+    }
+  }
+}
@@ -1,12 +1,19 @@
-use std::ops::Not;
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 
-use bevy::prelude::*;
+use crate::{
+  bird::{Bird, BirdDied, Dead},
+  input::InputAction,
+  rng::GameRng,
+};
 
 #[derive(States, Clone, Copy, Eq, PartialEq, Hash, Debug)]
 pub enum GameState {
+  MainMenu,
   Idle,
   Going,
   Paused,
+  /// The last bird has hit something and is falling to the ground.
+  Dying,
   GameOver,
 }
 
@@ -14,40 +21,112 @@ pub struct GameStatePlugin;
 
 impl Plugin for GameStatePlugin {
   fn build(&self, app: &mut App) {
-    app //
+    app
+      .add_event::<RunStarted>()
+      .init_schedule(OnRunStart)
       .add_systems(
-        FixedUpdate,
+        OnTransition {
+          exited: GameState::MainMenu,
+          entered: GameState::Going,
+        },
+        run_start_schedule,
+      )
+      .add_systems(
+        OnTransition {
+          exited: GameState::Idle,
+          entered: GameState::Going,
+        },
+        run_start_schedule,
+      )
+      .add_systems(
+        OnTransition {
+          exited: GameState::GameOver,
+          entered: GameState::Going,
+        },
+        run_start_schedule,
+      )
+      .init_resource::<RunTicks>()
+      .add_systems(OnRunStart, reset_run_ticks)
+      .add_systems(
+        Update,
         (
-          start_or_resume_game.run_if(not(in_state(GameState::Going))),
+          start_game.run_if(
+            in_state(GameState::Idle).or(in_state(GameState::GameOver)),
+          ),
           pause_game.run_if(in_state(GameState::Going)),
+          resume_game.run_if(in_state(GameState::Paused)),
+          kill_bird
+            .run_if(in_state(GameState::Going).and(on_event::<BirdDied>)),
         ),
+      )
+      .add_systems(
+        FixedLast,
+        advance_run_ticks.run_if(in_state(GameState::Going)),
       );
   }
 }
 
-#[derive(Default, Debug)]
-struct IsPaused(bool);
+/// Runs when a new run starts, that is when the game goes into
+/// [`GameState::Going`] from any state but [`GameState::Paused`].
+#[derive(ScheduleLabel, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct OnRunStart;
+
+/// Sent once [`OnRunStart`] has set up a new run.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct RunStarted {
+  pub seed: u64,
+}
+
+fn run_start_schedule(world: &mut World) {
+  world.run_schedule(OnRunStart);
+  let seed = world.resource::<GameRng>().seed();
+  world.send_event(RunStarted { seed });
+}
+
+/// Number of fixed ticks the current run has been going for.
+#[derive(Resource, Deref, Default, Debug)]
+pub struct RunTicks(u32);
+
+fn advance_run_ticks(mut ticks: ResMut<RunTicks>) {
+  ticks.0 += 1;
+}
+
+fn reset_run_ticks(mut ticks: ResMut<RunTicks>) {
+  ticks.0 = 0;
+}
 
-fn start_or_resume_game(
-  mut is_paused: Local<IsPaused>,
+fn start_game(
+  actions: Res<ButtonInput<InputAction>>,
   mut next_state: ResMut<NextState<GameState>>,
-  keys: Res<ButtonInput<KeyCode>>,
 ) {
-  if is_paused.0.not() {
-    if keys.pressed(KeyCode::Space).not() {
-      is_paused.0 = true;
-    }
-  } else if keys.pressed(KeyCode::Space) {
+  if actions.any_just_pressed([InputAction::Flap, InputAction::Confirm]) {
     next_state.set(GameState::Going);
-    is_paused.0 = false;
+  }
+}
+
+fn kill_bird(
+  mut next_state: ResMut<NextState<GameState>>,
+  alive_birds: Query<(), (With<Bird>, Without<Dead>)>,
+) {
+  if alive_birds.is_empty() {
+    next_state.set(GameState::Dying);
   }
 }
 
 fn pause_game(
+  actions: Res<ButtonInput<InputAction>>,
   mut next_state: ResMut<NextState<GameState>>,
-  keys: Res<ButtonInput<KeyCode>>,
 ) {
-  if keys.pressed(KeyCode::Escape) {
+  if actions.just_pressed(InputAction::Pause) {
     next_state.set(GameState::Paused);
   }
 }
+
+fn resume_game(
+  actions: Res<ButtonInput<InputAction>>,
+  mut next_state: ResMut<NextState<GameState>>,
+) {
+  if actions.any_just_pressed([InputAction::Pause, InputAction::Back]) {
+    next_state.set(GameState::Going);
+  }
+}
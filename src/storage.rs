@@ -0,0 +1,49 @@
+use std::{
+  fs,
+  io::{self, ErrorKind},
+  path::{Path, PathBuf},
+};
+
+use ron::ser::PrettyConfig;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Directory where the game keeps its files, if the platform has one.
+pub fn data_dir() -> Option<PathBuf> {
+  dirs::data_dir().map(|dir| dir.join("floppy_burp"))
+}
+
+/// Formats a unix timestamp as `YYYY-MM-DD`, in UTC.
+pub fn date_string(unix_secs: u64) -> String {
+  // civil from days algorithm by Howard Hinnant
+  let days = (unix_secs / 86400) as i64 + 719468;
+  let era = days.div_euclid(146097);
+  let day_of_era = days.rem_euclid(146097);
+  let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524
+    - day_of_era / 146096)
+    / 365;
+  let day_of_year =
+    day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let mp = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = year_of_era + era * 400 + i64::from(month <= 2);
+  format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Reads a value from a RON file.
+pub fn load<T: DeserializeOwned>(path: impl AsRef<Path>) -> io::Result<T> {
+  let contents = fs::read_to_string(path)?;
+  ron::from_str(&contents)
+    .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Writes a value to a RON file, creating missing parent directories.
+pub fn save<T: Serialize>(path: impl AsRef<Path>, value: &T) -> io::Result<()> {
+  let path = path.as_ref();
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  let contents = ron::ser::to_string_pretty(value, PrettyConfig::default())
+    .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+  fs::write(path, contents)
+}
@@ -1,7 +1,23 @@
 use bevy::{prelude::*, text::FontSmoothing};
 
-use self::{prompt::PromptUiPlugin, score::ScoreUiPlugin};
-use crate::{score::Score, state::GameState};
+use self::{
+  flash::FlashUiPlugin,
+  game_over::GameOverUiPlugin,
+  leaderboard::LeaderboardUiPlugin,
+  main_menu::MainMenuUiPlugin,
+  menu::MenuUiPlugin,
+  pause_menu::PauseMenuUiPlugin,
+  prompt::PromptUiPlugin,
+  score::ScoreUiPlugin,
+  settings::SettingsUiPlugin,
+  versus::VersusUiPlugin,
+};
+use crate::{
+  input::InputAction,
+  score::Score,
+  state::GameState,
+  versus::Versus,
+};
 
 pub struct UiPlugin;
 
@@ -9,7 +25,20 @@ impl Plugin for UiPlugin {
   fn build(&self, app: &mut App) {
     app //
       .init_resource::<GameFont>()
-      .add_plugins((PromptUiPlugin, ScoreUiPlugin));
+      .add_event::<OpenSettings>()
+      .add_event::<ToggleLeaderboard>()
+      .add_plugins((
+        MenuUiPlugin,
+        PromptUiPlugin,
+        ScoreUiPlugin,
+        GameOverUiPlugin,
+        FlashUiPlugin,
+        LeaderboardUiPlugin,
+        PauseMenuUiPlugin,
+        MainMenuUiPlugin,
+        SettingsUiPlugin,
+        VersusUiPlugin,
+      ));
   }
 }
 
@@ -29,6 +58,14 @@ impl FromWorld for GameFont {
   }
 }
 
+/// Asks for the settings screen to be shown.
+#[derive(Event, Debug)]
+struct OpenSettings;
+
+/// Shows the leaderboard panel, or hides it if it's shown.
+#[derive(Event, Debug)]
+struct ToggleLeaderboard;
+
 mod score {
   use super::*;
 
@@ -38,6 +75,12 @@ mod score {
     fn build(&self, app: &mut App) {
       app //
         .add_systems(Startup, init_score_text)
+        // the title screen has no score to show
+        .add_systems(OnEnter(GameState::MainMenu), hide_score_bar)
+        .add_systems(
+          OnExit(GameState::MainMenu),
+          show_score_bar.run_if(not(resource_exists::<Versus>)),
+        )
         .add_systems(
           Update,
           update_score_text.run_if(in_state(GameState::Going)),
@@ -45,19 +88,32 @@ mod score {
     }
   }
 
+  #[derive(Component)]
+  struct ScoreBar;
+
   #[derive(Component)]
   struct ScoreText;
 
-  fn init_score_text(mut commands: Commands, font: Res<GameFont>) {
+  fn init_score_text(
+    mut commands: Commands,
+    font: Res<GameFont>,
+    state: Res<State<GameState>>,
+  ) {
     let font = font.clone();
     commands
       .spawn((
+        ScoreBar,
         Text::new("Score "), //
         font.clone(),
         Node {
           margin: UiRect::left(Val::Px(5.0)),
           ..default()
         },
+        if *state == GameState::MainMenu {
+          Visibility::Hidden
+        } else {
+          Visibility::Inherited
+        },
       ))
       .with_child((
         TextSpan::new(format!("{}", *Score::default())),
@@ -66,6 +122,17 @@ mod score {
       ));
   }
 
+  fn show_score_bar(mut visibility: Single<&mut Visibility, With<ScoreBar>>) {
+    **visibility = Visibility::Inherited;
+  }
+
+  // runs before the score bar is spawned when the game starts in the menu
+  fn hide_score_bar(mut query: Query<&mut Visibility, With<ScoreBar>>) {
+    for mut visibility in &mut query {
+      *visibility = Visibility::Hidden;
+    }
+  }
+
   fn update_score_text(
     score: Res<Score>,
     mut text_span: Single<&mut TextSpan, With<ScoreText>>,
@@ -83,30 +150,28 @@ mod prompt {
 
   impl Plugin for PromptUiPlugin {
     fn build(&self, app: &mut App) {
-      app
-        .insert_resource(PromptAnimationTimer::from_seconds(0.7))
-        .add_systems(
-          OnEnter(GameState::Idle),
+      // the prompt is spawned on first use, as the game may not go through
+      // idle before a game over, and these may come before startup
+      let show_prompt_systems = || {
+        (
           (
-            (init_prompt, show_prompt).chain(),
-            reset_prompt_animation_timer,
-          ),
-        )
-        .add_systems(
-          OnEnter(GameState::GameOver),
-          (show_prompt, reset_prompt_animation_timer),
-        )
-        .add_systems(
-          OnEnter(GameState::Paused),
-          (show_prompt, reset_prompt_animation_timer),
+            init_prompt.run_if(not(any_with_component::<TextPrompt>)),
+            show_prompt,
+          )
+            .chain(),
+          reset_prompt_animation_timer,
         )
+      };
+      app
+        .insert_resource(PromptAnimationTimer::from_seconds(0.7))
+        .add_systems(OnEnter(GameState::Idle), show_prompt_systems())
+        .add_systems(OnEnter(GameState::GameOver), show_prompt_systems())
         .add_systems(OnEnter(GameState::Going), hide_prompt)
+        .add_systems(OnEnter(GameState::Paused), hide_prompt)
         .add_systems(
           Update,
           (tick_prompt_animation_timer, animate_prompt).run_if(
-            in_state(GameState::Idle)
-              .or(in_state(GameState::GameOver))
-              .or(in_state(GameState::Paused)),
+            in_state(GameState::Idle).or(in_state(GameState::GameOver)),
           ),
         );
     }
@@ -133,14 +198,21 @@ mod prompt {
 
   fn show_prompt(
     state: Res<State<GameState>>,
+    versus: Option<Res<Versus>>,
     query: Single<(&mut Text, &mut Visibility), With<TextPrompt>>,
   ) {
     let (mut text, mut visibility) = query.into_inner();
     let prompt = match state.get() {
-      GameState::Idle => "SPACE to fly\nESC to pause",
-      GameState::Paused => "SPACE to continue",
-      GameState::GameOver => "SPACE to restart",
-      GameState::Going => {
+      GameState::Idle if versus.is_some() => {
+        "SPACE and ENTER to fly\nESC to pause"
+      }
+      GameState::Idle => "SPACE to fly\nESC to pause\nL for top scores",
+      GameState::GameOver if versus.is_some() => "SPACE to restart",
+      GameState::GameOver => "SPACE to restart\nR to replay\nL for top scores",
+      GameState::MainMenu
+      | GameState::Going
+      | GameState::Paused
+      | GameState::Dying => {
         unreachable!("prompt shouldn't be displayed in this state")
       }
     };
@@ -180,7 +252,1421 @@ mod prompt {
     }
   }
 
-  fn hide_prompt(mut visibility: Single<&mut Visibility, With<TextPrompt>>) {
+  fn hide_prompt(mut query: Query<&mut Visibility, With<TextPrompt>>) {
+    for mut visibility in &mut query {
+      *visibility = Visibility::Hidden;
+    }
+  }
+}
+
+mod game_over {
+  use super::*;
+  use crate::{
+    leaderboard::{record_score, Leaderboard},
+    score::{Medal, MedalThresholds},
+  };
+
+  pub struct GameOverUiPlugin;
+
+  impl Plugin for GameOverUiPlugin {
+    fn build(&self, app: &mut App) {
+      app
+        .add_systems(Startup, init_game_over_panel)
+        .add_systems(
+          OnEnter(GameState::GameOver),
+          // the leaderboard still holds the previous best at this point
+          show_game_over_panel
+            .before(record_score)
+            .run_if(not(resource_exists::<Versus>)),
+        )
+        .add_systems(OnExit(GameState::GameOver), hide_game_over_panel)
+        .add_systems(
+          Update,
+          slide_game_over_panel.run_if(in_state(GameState::GameOver)),
+        );
+    }
+  }
+
+  #[derive(Component)]
+  struct GameOverPanel;
+
+  #[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+  enum GameOverText {
+    Score,
+    Best,
+    Medal,
+    NewBest,
+  }
+
+  #[derive(Component, Deref, DerefMut)]
+  struct SlideIn(Timer);
+
+  impl GameOverPanel {
+    const TOP: f32 = 20.0;
+    const SLIDE_SECS: f32 = 0.4;
+  }
+
+  fn init_game_over_panel(mut commands: Commands, font: Res<GameFont>) {
+    let small_font = TextFont {
+      font_size: 14.0,
+      ..font.clone()
+    };
+    commands
+      .spawn((
+        GameOverPanel,
+        Node {
+          position_type: PositionType::Absolute,
+          width: Val::Percent(100.),
+          top: Val::Percent(GameOverPanel::TOP),
+          justify_content: JustifyContent::Center,
+          ..default()
+        },
+        Visibility::Hidden,
+        SlideIn(Timer::from_seconds(
+          GameOverPanel::SLIDE_SECS,
+          TimerMode::Once,
+        )),
+      ))
+      .with_children(|parent| {
+        parent
+          .spawn((
+            Node {
+              flex_direction: FlexDirection::Column,
+              align_items: AlignItems::Center,
+              padding: UiRect::all(Val::Px(8.0)),
+              ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+          ))
+          .with_children(|panel| {
+            panel.spawn((Text::new("GAME OVER"), font.clone()));
+            for text in [
+              GameOverText::Score,
+              GameOverText::Best,
+              GameOverText::Medal,
+              GameOverText::NewBest,
+            ] {
+              panel.spawn((text, Text::default(), small_font.clone()));
+            }
+          });
+      });
+  }
+
+  fn show_game_over_panel(
+    score: Res<Score>,
+    leaderboard: Res<Leaderboard>,
+    medal_thresholds: Res<MedalThresholds>,
+    panel: Single<
+      (&mut Visibility, &mut Node, &mut SlideIn),
+      With<GameOverPanel>,
+    >,
+    mut texts: Query<(&GameOverText, &mut Text, &mut TextColor)>,
+  ) {
+    let previous_best = leaderboard.best_score().unwrap_or(0);
+    let medal = medal_thresholds.medal(**score);
+    for (text_kind, mut text, mut color) in &mut texts {
+      text.0 = match text_kind {
+        GameOverText::Score => format!("SCORE {}", **score),
+        GameOverText::Best => {
+          format!("BEST {}", previous_best.max(**score))
+        }
+        GameOverText::Medal => {
+          medal.map_or_else(String::new, |medal| format!("{medal} MEDAL"))
+        }
+        GameOverText::NewBest if **score > previous_best => "NEW BEST!".into(),
+        GameOverText::NewBest => String::new(),
+      };
+      if *text_kind == GameOverText::Medal {
+        if let Some(medal) = medal {
+          color.0 = medal_color(medal);
+        }
+      }
+    }
+    let (mut visibility, mut node, mut slide_in) = panel.into_inner();
+    *visibility = Visibility::Inherited;
+    node.top = Val::Percent(100.);
+    slide_in.reset();
+  }
+
+  fn medal_color(medal: Medal) -> Color {
+    match medal {
+      Medal::Bronze => Color::srgb(0.8, 0.5, 0.2),
+      Medal::Silver => Color::srgb(0.75, 0.75, 0.8),
+      Medal::Gold => Color::srgb(1.0, 0.84, 0.0),
+      Medal::Platinum => Color::srgb(0.85, 0.95, 1.0),
+    }
+  }
+
+  fn slide_game_over_panel(
+    time: Res<Time>,
+    panel: Single<(&mut Node, &mut SlideIn), With<GameOverPanel>>,
+  ) {
+    let (mut node, mut slide_in) = panel.into_inner();
+    if slide_in.finished() {
+      return;
+    }
+    slide_in.tick(time.delta());
+    let eased = 1.0 - (1.0 - slide_in.fraction()).powi(3);
+    node.top = Val::Percent(100.0.lerp(GameOverPanel::TOP, eased));
+  }
+
+  fn hide_game_over_panel(
+    mut visibility: Single<&mut Visibility, With<GameOverPanel>>,
+  ) {
+    **visibility = Visibility::Hidden;
+  }
+}
+
+mod flash {
+  use super::*;
+
+  pub struct FlashUiPlugin;
+
+  impl Plugin for FlashUiPlugin {
+    fn build(&self, app: &mut App) {
+      app
+        .add_systems(Startup, init_flash)
+        .add_systems(OnEnter(GameState::Dying), show_flash)
+        .add_systems(Update, fade_flash);
+    }
+  }
+
+  /// White screen shown for a moment when the bird gets hit.
+  #[derive(Component)]
+  struct Flash;
+
+  #[derive(Component, Deref, DerefMut)]
+  struct FadeOut(Timer);
+
+  impl Flash {
+    const FADE_SECS: f32 = 0.3;
+  }
+
+  fn init_flash(mut commands: Commands) {
+    commands.spawn((
+      Flash,
+      Node {
+        position_type: PositionType::Absolute,
+        width: Val::Percent(100.),
+        height: Val::Percent(100.),
+        ..default()
+      },
+      BackgroundColor(Color::WHITE),
+      Visibility::Hidden,
+      FadeOut(Timer::from_seconds(Flash::FADE_SECS, TimerMode::Once)),
+    ));
+  }
+
+  fn show_flash(flash: Single<(&mut Visibility, &mut FadeOut), With<Flash>>) {
+    let (mut visibility, mut fade_out) = flash.into_inner();
+    *visibility = Visibility::Inherited;
+    fade_out.reset();
+  }
+
+  fn fade_flash(
+    time: Res<Time>,
+    flash: Single<
+      (&mut Visibility, &mut BackgroundColor, &mut FadeOut),
+      With<Flash>,
+    >,
+  ) {
+    let (mut visibility, mut color, mut fade_out) = flash.into_inner();
+    if *visibility == Visibility::Hidden {
+      return;
+    }
+    fade_out.tick(time.delta());
+    color.0.set_alpha(fade_out.fraction_remaining());
+    if fade_out.finished() {
+      *visibility = Visibility::Hidden;
+    }
+  }
+}
+
+mod versus {
+  use super::*;
+  use crate::{
+    bird::{Bird, Player},
+    score::BirdScore,
+  };
+
+  pub struct VersusUiPlugin;
+
+  impl Plugin for VersusUiPlugin {
+    fn build(&self, app: &mut App) {
+      app
+        .add_systems(Startup, init_versus_ui)
+        .add_systems(
+          OnExit(GameState::MainMenu),
+          show_player_scores.run_if(resource_exists::<Versus>),
+        )
+        .add_systems(OnEnter(GameState::MainMenu), hide_player_scores)
+        .add_systems(
+          Update,
+          update_player_scores.run_if(resource_exists::<Versus>),
+        )
+        .add_systems(
+          OnEnter(GameState::GameOver),
+          show_winner_panel.run_if(resource_exists::<Versus>),
+        )
+        .add_systems(OnExit(GameState::GameOver), hide_winner_panel);
+    }
+  }
+
+  /// Scores of both players, shown instead of the score bar.
+  #[derive(Component)]
+  struct PlayerScores;
+
+  #[derive(Component)]
+  struct PlayerScoreText(Player);
+
+  /// Announces who won once both birds are down.
+  #[derive(Component)]
+  struct WinnerPanel;
+
+  #[derive(Component)]
+  struct WinnerText;
+
+  fn player_name(player: Player) -> &'static str {
+    match player {
+      Player::One => "P1",
+      Player::Two => "P2",
+    }
+  }
+
+  fn init_versus_ui(mut commands: Commands, font: Res<GameFont>) {
+    commands
+      .spawn((
+        PlayerScores,
+        Node {
+          margin: UiRect::horizontal(Val::Px(5.0)),
+          column_gap: Val::Px(12.0),
+          ..default()
+        },
+        Visibility::Hidden,
+      ))
+      .with_children(|parent| {
+        for player in Player::ALL {
+          parent.spawn((
+            PlayerScoreText(player),
+            Text::default(),
+            font.clone(),
+            TextColor(player.color()),
+          ));
+        }
+      });
+    commands
+      .spawn((
+        WinnerPanel,
+        Node {
+          position_type: PositionType::Absolute,
+          width: Val::Percent(100.),
+          top: Val::Percent(30.),
+          justify_content: JustifyContent::Center,
+          ..default()
+        },
+        Visibility::Hidden,
+      ))
+      .with_child((
+        WinnerText,
+        Text::default(),
+        TextLayout::new_with_justify(JustifyText::Center),
+        font.clone(),
+        Node {
+          padding: UiRect::all(Val::Px(8.0)),
+          ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+      ));
+  }
+
+  fn show_player_scores(
+    mut visibility: Single<&mut Visibility, With<PlayerScores>>,
+  ) {
+    **visibility = Visibility::Inherited;
+  }
+
+  // runs before the scores are spawned when the game starts in the menu
+  fn hide_player_scores(mut query: Query<&mut Visibility, With<PlayerScores>>) {
+    for mut visibility in &mut query {
+      *visibility = Visibility::Hidden;
+    }
+  }
+
+  fn update_player_scores(
+    birds: Query<(&Player, Ref<BirdScore>), With<Bird>>,
+    mut texts: Query<(&PlayerScoreText, &mut Text)>,
+  ) {
+    for (player, score) in &birds {
+      if !score.is_changed() {
+        continue;
+      }
+      for (text_player, mut text) in &mut texts {
+        if text_player.0 == *player {
+          text.0 = format!("{} {}", player_name(*player), **score);
+        }
+      }
+    }
+  }
+
+  fn show_winner_panel(
+    versus: Res<Versus>,
+    mut panel: Single<&mut Visibility, With<WinnerPanel>>,
+    text: Single<(&mut Text, &mut TextColor), With<WinnerText>>,
+  ) {
+    let (mut text, mut color) = text.into_inner();
+    match versus.winner {
+      Some(player) => {
+        text.0 = format!("{} WINS!", player_name(player));
+        color.0 = player.color();
+      }
+      None => {
+        text.0 = "DRAW".into();
+        color.0 = Color::WHITE;
+      }
+    }
+    **panel = Visibility::Inherited;
+  }
+
+  fn hide_winner_panel(
+    mut visibility: Single<&mut Visibility, With<WinnerPanel>>,
+  ) {
+    **visibility = Visibility::Hidden;
+  }
+}
+
+mod leaderboard {
+  use super::*;
+  use crate::leaderboard::Leaderboard;
+
+  pub struct LeaderboardUiPlugin;
+
+  impl Plugin for LeaderboardUiPlugin {
+    fn build(&self, app: &mut App) {
+      app
+        .add_systems(Startup, init_leaderboard_panel)
+        .add_systems(OnEnter(GameState::Going), hide_leaderboard_panel)
+        .add_systems(
+          Update,
+          (
+            toggle_leaderboard_panel.run_if(
+              in_state(GameState::MainMenu)
+                .or(in_state(GameState::Idle))
+                .or(in_state(GameState::GameOver)),
+            ),
+            update_leaderboard_text.run_if(resource_changed::<Leaderboard>),
+          ),
+        );
+    }
+  }
+
+  #[derive(Component)]
+  struct LeaderboardPanel;
+
+  #[derive(Component)]
+  struct LeaderboardText;
+
+  fn init_leaderboard_panel(mut commands: Commands, font: Res<GameFont>) {
+    commands
+      .spawn((
+        LeaderboardPanel,
+        Node {
+          position_type: PositionType::Absolute,
+          width: Val::Percent(100.),
+          top: Val::Percent(15.),
+          justify_content: JustifyContent::Center,
+          ..default()
+        },
+        Visibility::Hidden,
+        GlobalZIndex(1),
+      ))
+      .with_child((
+        LeaderboardText,
+        Text::default(),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont {
+          font_size: 14.0,
+          ..font.clone()
+        },
+        Node {
+          padding: UiRect::all(Val::Px(6.0)),
+          ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+      ));
+  }
+
+  fn update_leaderboard_text(
+    leaderboard: Res<Leaderboard>,
+    mut text: Single<&mut Text, With<LeaderboardText>>,
+  ) {
+    let mut table = String::from("TOP SCORES");
+    if leaderboard.entries().is_empty() {
+      table.push_str("\nnothing yet");
+    }
+    for (rank, entry) in leaderboard.entries().iter().enumerate() {
+      let duration = entry.duration_secs as u32;
+      table.push_str(&format!(
+        "\n{}. {}  {}  {}:{:02}",
+        rank + 1,
+        entry.score,
+        entry.date_string(),
+        duration / 60,
+        duration % 60,
+      ));
+    }
+    text.0 = table;
+  }
+
+  fn toggle_leaderboard_panel(
+    actions: Res<ButtonInput<InputAction>>,
+    mut events: EventReader<ToggleLeaderboard>,
+    mut visibility: Single<&mut Visibility, With<LeaderboardPanel>>,
+  ) {
+    let toggles = events.read().count()
+      + usize::from(actions.just_pressed(InputAction::Leaderboard));
+    if toggles % 2 == 1 {
+      visibility.toggle_inherited_hidden();
+    } else if toggles == 0 && actions.just_pressed(InputAction::Back) {
+      **visibility = Visibility::Hidden;
+    }
+  }
+
+  fn hide_leaderboard_panel(
+    mut visibility: Single<&mut Visibility, With<LeaderboardPanel>>,
+  ) {
     **visibility = Visibility::Hidden;
   }
 }
+
+mod menu {
+  use bevy::ecs::system::EntityCommands;
+
+  use super::*;
+
+  /// Handles focus and activation of every [`Menu`]. Only visible menus
+  /// respond to input.
+  pub struct MenuUiPlugin;
+
+  impl Plugin for MenuUiPlugin {
+    fn build(&self, app: &mut App) {
+      app.add_event::<MenuActivated>().add_systems(
+        Update,
+        (
+          (move_focus, focus_hovered_entry),
+          highlight_focused_entry,
+          activate_entry,
+        )
+          .chain(),
+      );
+    }
+  }
+
+  /// Root of a list of [`MenuEntry`]s, one of which is focused.
+  #[derive(Component, Default)]
+  pub struct Menu {
+    pub focus: usize,
+  }
+
+  #[derive(Component, Clone, Copy)]
+  pub struct MenuEntry {
+    pub menu: Entity,
+    pub index: usize,
+  }
+
+  /// Sent with the entry that got chosen with the confirm action or a click.
+  #[derive(Event, Debug)]
+  pub struct MenuActivated(pub Entity);
+
+  const FOCUSED_COLOR: Color = Color::srgb(1.0, 0.84, 0.0);
+
+  /// Spawns the `index`th entry of `menu`. Menus read what the entry does
+  /// from components inserted into it.
+  pub fn spawn_entry<'a>(
+    parent: &'a mut ChildBuilder,
+    font: &GameFont,
+    menu: Entity,
+    index: usize,
+    label: impl Into<String>,
+  ) -> EntityCommands<'a> {
+    parent.spawn((
+      MenuEntry { menu, index },
+      Button,
+      Text::new(label),
+      TextFont {
+        font_size: 14.0,
+        ..font.0.clone()
+      },
+    ))
+  }
+
+  fn move_focus(
+    actions: Res<ButtonInput<InputAction>>,
+    mut menus: Query<(Entity, &mut Menu, &InheritedVisibility)>,
+    entries: Query<&MenuEntry>,
+  ) {
+    let up = actions.just_pressed(InputAction::Up);
+    let down = actions.just_pressed(InputAction::Down);
+    if !up && !down {
+      return;
+    }
+    for (entity, mut menu, visibility) in &mut menus {
+      let len = entries.iter().filter(|e| e.menu == entity).count();
+      if !visibility.get() || len == 0 {
+        continue;
+      }
+      if up {
+        menu.focus = (menu.focus + len - 1) % len;
+      }
+      if down {
+        menu.focus = (menu.focus + 1) % len;
+      }
+    }
+  }
+
+  fn focus_hovered_entry(
+    mut menus: Query<&mut Menu>,
+    entries: Query<(&MenuEntry, &Interaction), Changed<Interaction>>,
+  ) {
+    for (entry, interaction) in &entries {
+      if *interaction == Interaction::None {
+        continue;
+      }
+      if let Ok(mut menu) = menus.get_mut(entry.menu) {
+        menu.focus = entry.index;
+      }
+    }
+  }
+
+  fn highlight_focused_entry(
+    menus: Query<&Menu>,
+    mut entries: Query<(&MenuEntry, &mut TextColor)>,
+  ) {
+    for (entry, mut color) in &mut entries {
+      let focused = menus
+        .get(entry.menu)
+        .is_ok_and(|menu| menu.focus == entry.index);
+      let new_color = if focused { FOCUSED_COLOR } else { Color::WHITE };
+      if color.0 != new_color {
+        color.0 = new_color;
+      }
+    }
+  }
+
+  fn activate_entry(
+    actions: Res<ButtonInput<InputAction>>,
+    menus: Query<(&Menu, &InheritedVisibility)>,
+    entries: Query<(Entity, &MenuEntry, Ref<Interaction>)>,
+    mut events: EventWriter<MenuActivated>,
+  ) {
+    for (entity, entry, interaction) in &entries {
+      let Ok((menu, visibility)) = menus.get(entry.menu) else {
+        continue;
+      };
+      if !visibility.get() || menu.focus != entry.index {
+        continue;
+      }
+      let clicked =
+        interaction.is_changed() && *interaction == Interaction::Pressed;
+      if clicked || actions.just_pressed(InputAction::Confirm) {
+        events.send(MenuActivated(entity));
+      }
+    }
+  }
+}
+
+mod pause_menu {
+  use super::{
+    menu::{spawn_entry, Menu, MenuActivated},
+    *,
+  };
+
+  pub struct PauseMenuUiPlugin;
+
+  impl Plugin for PauseMenuUiPlugin {
+    fn build(&self, app: &mut App) {
+      app
+        .add_systems(Startup, init_pause_menu)
+        .add_systems(OnEnter(GameState::Paused), show_pause_menu)
+        .add_systems(OnExit(GameState::Paused), hide_pause_menu)
+        .add_systems(
+          Update,
+          activate_pause_menu_item.run_if(in_state(GameState::Paused)),
+        );
+    }
+  }
+
+  #[derive(Component)]
+  struct PauseMenu;
+
+  #[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+  enum PauseMenuItem {
+    Resume,
+    Restart,
+    Settings,
+    Quit,
+  }
+
+  impl PauseMenuItem {
+    const ALL: [Self; 4] =
+      [Self::Resume, Self::Restart, Self::Settings, Self::Quit];
+
+    fn label(self) -> &'static str {
+      match self {
+        Self::Resume => "RESUME",
+        Self::Restart => "RESTART",
+        Self::Settings => "SETTINGS",
+        Self::Quit => "QUIT TO TITLE",
+      }
+    }
+  }
+
+  fn init_pause_menu(mut commands: Commands, font: Res<GameFont>) {
+    let menu = commands
+      .spawn((
+        PauseMenu,
+        Menu::default(),
+        Node {
+          position_type: PositionType::Absolute,
+          width: Val::Percent(100.),
+          height: Val::Percent(100.),
+          justify_content: JustifyContent::Center,
+          align_items: AlignItems::Center,
+          ..default()
+        },
+        Visibility::Hidden,
+      ))
+      .id();
+    commands.entity(menu).with_children(|parent| {
+      parent
+        .spawn((
+          Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+          },
+          BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        ))
+        .with_children(|panel| {
+          panel.spawn((Text::new("PAUSED"), font.clone()));
+          for (index, item) in PauseMenuItem::ALL.into_iter().enumerate() {
+            spawn_entry(panel, &font, menu, index, item.label()).insert(item);
+          }
+        });
+    });
+  }
+
+  fn show_pause_menu(
+    query: Single<(&mut Visibility, &mut Menu), With<PauseMenu>>,
+  ) {
+    let (mut visibility, mut menu) = query.into_inner();
+    *visibility = Visibility::Inherited;
+    menu.focus = 0;
+  }
+
+  fn hide_pause_menu(mut visibility: Single<&mut Visibility, With<PauseMenu>>) {
+    **visibility = Visibility::Hidden;
+  }
+
+  fn activate_pause_menu_item(
+    mut events: EventReader<MenuActivated>,
+    items: Query<&PauseMenuItem>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut settings_events: EventWriter<OpenSettings>,
+  ) {
+    for item in events.read().filter_map(|e| items.get(e.0).ok()) {
+      match item {
+        PauseMenuItem::Resume => next_state.set(GameState::Going),
+        PauseMenuItem::Restart => next_state.set(GameState::Idle),
+        PauseMenuItem::Settings => {
+          settings_events.send(OpenSettings);
+        }
+        PauseMenuItem::Quit => next_state.set(GameState::MainMenu),
+      }
+    }
+  }
+}
+
+mod main_menu {
+  use std::path::PathBuf;
+
+  use super::{
+    menu::{spawn_entry, Menu, MenuActivated},
+    *,
+  };
+  use crate::{
+    autopilot::start_demo,
+    replay::{Replay, ReplayDir, StartReplay},
+    storage,
+    versus::start_versus,
+  };
+
+  pub struct MainMenuUiPlugin;
+
+  impl Plugin for MainMenuUiPlugin {
+    fn build(&self, app: &mut App) {
+      app
+        .add_systems(
+          OnEnter(GameState::MainMenu),
+          (
+            init_main_menu.run_if(not(any_with_component::<MainMenu>)),
+            show_main_menu,
+          )
+            .chain(),
+        )
+        .add_systems(OnExit(GameState::MainMenu), hide_main_menu)
+        .add_systems(
+          Update,
+          (
+            bob_title,
+            start_demo_when_idle,
+            activate_main_menu_item,
+            (activate_replay_list_entry, close_replay_list).chain(),
+          )
+            .run_if(in_state(GameState::MainMenu)),
+        );
+    }
+  }
+
+  #[derive(Component)]
+  struct Title;
+
+  #[derive(Component)]
+  struct MainMenu;
+
+  #[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+  enum MainMenuItem {
+    Play,
+    Versus,
+    Leaderboard,
+    Settings,
+    Replays,
+    Quit,
+  }
+
+  impl MainMenuItem {
+    const ALL: [Self; 6] = [
+      Self::Play,
+      Self::Versus,
+      Self::Leaderboard,
+      Self::Settings,
+      Self::Replays,
+      Self::Quit,
+    ];
+
+    fn label(self) -> &'static str {
+      match self {
+        Self::Play => "PLAY",
+        Self::Versus => "VERSUS",
+        Self::Leaderboard => "TOP SCORES",
+        Self::Settings => "SETTINGS",
+        Self::Replays => "REPLAYS",
+        Self::Quit => "QUIT",
+      }
+    }
+  }
+
+  /// Saved replays to pick one to play back from.
+  #[derive(Component)]
+  struct ReplayList;
+
+  #[derive(Component)]
+  struct ReplayListPanel;
+
+  #[derive(Component)]
+  enum ReplayListEntry {
+    Replay(PathBuf),
+    Back,
+  }
+
+  impl Title {
+    const TOP: f32 = 12.0;
+  }
+
+  impl MainMenu {
+    /// How long the main menu waits for input before the autopilot plays a
+    /// demo run.
+    const IDLE_SECS: f32 = 20.0;
+  }
+
+  impl ReplayList {
+    /// How many of the newest replays are listed.
+    const LEN: usize = 8;
+  }
+
+  fn init_main_menu(mut commands: Commands, font: Res<GameFont>) {
+    commands.spawn((
+      Title,
+      Text::new("FLOPPY BURP"),
+      TextFont {
+        font_size: 40.0,
+        ..font.clone()
+      },
+      TextLayout::new_with_justify(JustifyText::Center),
+      Node {
+        position_type: PositionType::Absolute,
+        width: Val::Percent(100.),
+        top: Val::Percent(Title::TOP),
+        ..default()
+      },
+    ));
+    let menu = commands
+      .spawn((MainMenu, Menu::default(), menu_node()))
+      .id();
+    commands.entity(menu).with_children(|parent| {
+      parent.spawn(panel_node()).with_children(|panel| {
+        for (index, item) in MainMenuItem::ALL.into_iter().enumerate() {
+          spawn_entry(panel, &font, menu, index, item.label()).insert(item);
+        }
+      });
+    });
+    commands
+      .spawn((ReplayList, Menu::default(), menu_node(), Visibility::Hidden))
+      .with_child((ReplayListPanel, panel_node()));
+  }
+
+  fn start_demo_when_idle(
+    mut commands: Commands,
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    actions: Res<ButtonInput<InputAction>>,
+    menu: Single<&InheritedVisibility, With<MainMenu>>,
+    mut idle_secs: Local<f32>,
+  ) {
+    // the wait starts over whenever the main menu is entered, left for a
+    // submenu or used
+    if state.is_changed()
+      || !menu.get()
+      || actions.get_pressed().next().is_some()
+    {
+      *idle_secs = 0.0;
+      return;
+    }
+    *idle_secs += time.delta_secs();
+    if *idle_secs >= MainMenu::IDLE_SECS {
+      *idle_secs = 0.0;
+      commands.run_system_cached(start_demo);
+    }
+  }
+
+  fn menu_node() -> Node {
+    Node {
+      position_type: PositionType::Absolute,
+      width: Val::Percent(100.),
+      top: Val::Percent(40.),
+      justify_content: JustifyContent::Center,
+      ..default()
+    }
+  }
+
+  fn panel_node() -> impl Bundle {
+    (
+      Node {
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        row_gap: Val::Px(4.0),
+        padding: UiRect::all(Val::Px(8.0)),
+        ..default()
+      },
+      BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+    )
+  }
+
+  fn show_main_menu(
+    mut title: Single<&mut Visibility, With<Title>>,
+    menu: Single<
+      (&mut Visibility, &mut Menu),
+      (With<MainMenu>, Without<Title>),
+    >,
+  ) {
+    **title = Visibility::Inherited;
+    let (mut visibility, mut menu) = menu.into_inner();
+    *visibility = Visibility::Inherited;
+    menu.focus = 0;
+  }
+
+  fn hide_main_menu(
+    mut query: Query<
+      &mut Visibility,
+      Or<(With<Title>, With<MainMenu>, With<ReplayList>)>,
+    >,
+  ) {
+    for mut visibility in &mut query {
+      *visibility = Visibility::Hidden;
+    }
+  }
+
+  fn bob_title(time: Res<Time>, mut node: Single<&mut Node, With<Title>>) {
+    node.top = Val::Percent(Title::TOP + (time.elapsed_secs() * 2.0).sin());
+  }
+
+  fn activate_main_menu_item(
+    mut commands: Commands,
+    mut events: EventReader<MenuActivated>,
+    items: Query<&MainMenuItem>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut leaderboard_events: EventWriter<ToggleLeaderboard>,
+    mut settings_events: EventWriter<OpenSettings>,
+    mut app_exit_events: EventWriter<AppExit>,
+  ) {
+    for item in events.read().filter_map(|e| items.get(e.0).ok()) {
+      match item {
+        MainMenuItem::Play => next_state.set(GameState::Idle),
+        MainMenuItem::Versus => commands.run_system_cached(start_versus),
+        MainMenuItem::Leaderboard => {
+          leaderboard_events.send(ToggleLeaderboard);
+        }
+        MainMenuItem::Settings => {
+          settings_events.send(OpenSettings);
+        }
+        MainMenuItem::Replays => commands.run_system_cached(open_replay_list),
+        MainMenuItem::Quit => {
+          app_exit_events.send(AppExit::Success);
+        }
+      }
+    }
+  }
+
+  fn open_replay_list(
+    mut commands: Commands,
+    font: Res<GameFont>,
+    replay_dir: Res<ReplayDir>,
+    mut main_menu: Single<&mut Visibility, With<MainMenu>>,
+    list: Single<
+      (Entity, &mut Visibility, &mut Menu),
+      (With<ReplayList>, Without<MainMenu>),
+    >,
+    panel: Single<Entity, With<ReplayListPanel>>,
+  ) {
+    let paths = replay_dir.saved_replays().unwrap_or_else(|e| {
+      error!("failed to list replays: {e}");
+      Vec::new()
+    });
+    **main_menu = Visibility::Hidden;
+    let (menu, mut visibility, mut list) = list.into_inner();
+    *visibility = Visibility::Inherited;
+    list.focus = 0;
+    let mut panel = commands.entity(*panel);
+    panel.despawn_descendants().with_children(|panel| {
+      panel.spawn((Text::new("REPLAYS"), font.clone()));
+      let paths = paths.into_iter().take(ReplayList::LEN);
+      let mut len = 0;
+      for (index, path) in paths.enumerate() {
+        let label = ReplayDir::saved_at(&path).map_or_else(
+          || path.display().to_string(),
+          |secs| {
+            format!(
+              "{} {:02}:{:02}",
+              storage::date_string(secs),
+              secs / 3600 % 24,
+              secs / 60 % 60,
+            )
+          },
+        );
+        spawn_entry(panel, &font, menu, index, label)
+          .insert(ReplayListEntry::Replay(path));
+        len += 1;
+      }
+      if len == 0 {
+        panel.spawn((
+          Text::new("NO REPLAYS YET"),
+          TextFont {
+            font_size: 14.0,
+            ..font.clone()
+          },
+        ));
+      }
+      spawn_entry(panel, &font, menu, len, "BACK")
+        .insert(ReplayListEntry::Back);
+    });
+  }
+
+  fn activate_replay_list_entry(
+    mut commands: Commands,
+    mut events: EventReader<MenuActivated>,
+    entries: Query<&ReplayListEntry>,
+    mut replay_events: EventWriter<StartReplay>,
+  ) {
+    for entry in events.read().filter_map(|e| entries.get(e.0).ok()) {
+      match entry {
+        ReplayListEntry::Replay(path) => match Replay::load(path) {
+          Ok(replay) => {
+            replay_events.send(StartReplay(replay));
+          }
+          Err(e) => error!("failed to load {}: {e}", path.display()),
+        },
+        ReplayListEntry::Back => commands.run_system_cached(hide_replay_list),
+      }
+    }
+  }
+
+  fn close_replay_list(
+    mut commands: Commands,
+    actions: Res<ButtonInput<InputAction>>,
+    list: Single<&InheritedVisibility, With<ReplayList>>,
+  ) {
+    if list.get() && actions.just_pressed(InputAction::Back) {
+      commands.run_system_cached(hide_replay_list);
+    }
+  }
+
+  fn hide_replay_list(
+    mut main_menu: Single<&mut Visibility, With<MainMenu>>,
+    mut list: Single<&mut Visibility, (With<ReplayList>, Without<MainMenu>)>,
+  ) {
+    **list = Visibility::Hidden;
+    **main_menu = Visibility::Inherited;
+  }
+}
+
+mod settings {
+  use super::{
+    menu::{spawn_entry, Menu, MenuActivated, MenuEntry},
+    *,
+  };
+  use crate::{
+    input::{update_actions, InputBinding},
+    settings::{Settings, Theme},
+  };
+
+  pub struct SettingsUiPlugin;
+
+  impl Plugin for SettingsUiPlugin {
+    fn build(&self, app: &mut App) {
+      app
+        .init_resource::<SettingsScreen>()
+        .add_systems(Startup, init_settings_menu)
+        // these consume the inputs they handle before anything else sees them
+        .add_systems(
+          PreUpdate,
+          (capture_binding, close_settings_menu)
+            .chain()
+            .after(update_actions),
+        )
+        .add_systems(
+          Update,
+          (
+            open_settings_menu,
+            adjust_focused_setting,
+            activate_setting,
+            update_setting_labels,
+          )
+            .chain(),
+        );
+    }
+  }
+
+  #[derive(Component)]
+  struct SettingsMenu;
+
+  #[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+  enum SettingsEntry {
+    MasterVolume,
+    EffectsVolume,
+    MusicVolume,
+    ScaleFactor,
+    Vsync,
+    Theme,
+    Binding(InputAction),
+    Back,
+  }
+
+  impl SettingsEntry {
+    const ALL: [Self; 10] = [
+      Self::MasterVolume,
+      Self::EffectsVolume,
+      Self::MusicVolume,
+      Self::ScaleFactor,
+      Self::Vsync,
+      Self::Theme,
+      Self::Binding(InputAction::Flap),
+      Self::Binding(InputAction::Flap2),
+      Self::Binding(InputAction::Pause),
+      Self::Back,
+    ];
+
+    fn label(self, settings: &Settings, screen: &SettingsScreen) -> String {
+      let volume = |volume: f32| (volume * 10.0).round();
+      match self {
+        Self::MasterVolume => {
+          format!("VOLUME {}", volume(settings.master_volume))
+        }
+        Self::EffectsVolume => {
+          format!("EFFECTS {}", volume(settings.effects_volume))
+        }
+        Self::MusicVolume => format!("MUSIC {}", volume(settings.music_volume)),
+        Self::ScaleFactor => format!("SCALE {}", settings.scale_factor),
+        Self::Vsync => {
+          format!("VSYNC {}", if settings.vsync { "ON" } else { "OFF" })
+        }
+        Self::Theme => format!("THEME {}", theme_name(settings.theme)),
+        Self::Binding(action) => {
+          let key = if screen.rebinding == Some(action) {
+            "...".into()
+          } else {
+            settings
+              .bindings
+              .get(action)
+              .iter()
+              .find_map(|binding| match binding {
+                InputBinding::Key(key) => Some(key_name(*key)),
+                _ => None,
+              })
+              .unwrap_or_else(|| "NONE".into())
+          };
+          format!("{} KEY {key}", format!("{action:?}").to_uppercase())
+        }
+        Self::Back => "BACK".into(),
+      }
+    }
+
+    /// Changes the setting by `delta` steps, wrapping around at the ends if
+    /// `wrap` is set.
+    fn step(self, settings: &mut Settings, delta: i32, wrap: bool) {
+      let step = |value: i32, min: i32, max: i32| {
+        if wrap {
+          (value - min + delta).rem_euclid(max - min + 1) + min
+        } else {
+          (value + delta).clamp(min, max)
+        }
+      };
+      let step_volume = |volume: &mut f32| {
+        *volume = step((*volume * 10.0).round() as i32, 0, 10) as f32 / 10.0
+      };
+      match self {
+        Self::MasterVolume => step_volume(&mut settings.master_volume),
+        Self::EffectsVolume => step_volume(&mut settings.effects_volume),
+        Self::MusicVolume => step_volume(&mut settings.music_volume),
+        Self::ScaleFactor => {
+          settings.scale_factor = step(
+            settings.scale_factor.round() as i32,
+            Settings::MIN_SCALE_FACTOR as i32,
+            Settings::MAX_SCALE_FACTOR as i32,
+          ) as f32;
+        }
+        Self::Vsync => settings.vsync = !settings.vsync,
+        Self::Theme => {
+          let index = Theme::ALL.iter().position(|t| *t == settings.theme);
+          let last = Theme::ALL.len() as i32 - 1;
+          settings.theme =
+            Theme::ALL[step(index.unwrap_or(0) as i32, 0, last) as usize];
+        }
+        Self::Binding(_) | Self::Back => {}
+      }
+    }
+  }
+
+  fn theme_name(theme: Theme) -> &'static str {
+    match theme {
+      Theme::Day => "DAY",
+      Theme::Dusk => "DUSK",
+      Theme::Night => "NIGHT",
+    }
+  }
+
+  fn key_name(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    let name = name
+      .strip_prefix("Key")
+      .or_else(|| name.strip_prefix("Digit"))
+      .unwrap_or(&name);
+    name.to_uppercase()
+  }
+
+  #[derive(Resource, Default)]
+  struct SettingsScreen {
+    /// Menus hidden by the settings menu, shown again when it closes.
+    covered_menus: Vec<Entity>,
+    /// Action waiting for a key to be pressed to get bound to it.
+    rebinding: Option<InputAction>,
+  }
+
+  fn init_settings_menu(mut commands: Commands, font: Res<GameFont>) {
+    let menu = commands
+      .spawn((
+        SettingsMenu,
+        Menu::default(),
+        Node {
+          position_type: PositionType::Absolute,
+          width: Val::Percent(100.),
+          height: Val::Percent(100.),
+          justify_content: JustifyContent::Center,
+          align_items: AlignItems::Center,
+          ..default()
+        },
+        Visibility::Hidden,
+        GlobalZIndex(2),
+      ))
+      .id();
+    commands.entity(menu).with_children(|parent| {
+      parent
+        .spawn((
+          Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+          },
+          BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        ))
+        .with_children(|panel| {
+          panel.spawn((Text::new("SETTINGS"), font.clone()));
+          for (index, entry) in SettingsEntry::ALL.into_iter().enumerate() {
+            spawn_entry(panel, &font, menu, index, "").insert(entry);
+          }
+        });
+    });
+  }
+
+  fn open_settings_menu(
+    mut events: EventReader<OpenSettings>,
+    mut screen: ResMut<SettingsScreen>,
+    settings_menu: Single<
+      (&mut Visibility, &mut Menu, &InheritedVisibility),
+      With<SettingsMenu>,
+    >,
+    mut menus: Query<
+      (Entity, &mut Visibility, &InheritedVisibility),
+      (With<Menu>, Without<SettingsMenu>),
+    >,
+  ) {
+    if events.read().count() == 0 {
+      return;
+    }
+    let (mut visibility, mut menu, inherited_visibility) =
+      settings_menu.into_inner();
+    if inherited_visibility.get() {
+      return;
+    }
+    *visibility = Visibility::Inherited;
+    menu.focus = 0;
+    screen.covered_menus.clear();
+    for (entity, mut visibility, inherited_visibility) in &mut menus {
+      if inherited_visibility.get() {
+        *visibility = Visibility::Hidden;
+        screen.covered_menus.push(entity);
+      }
+    }
+  }
+
+  fn close_settings_menu(
+    mut commands: Commands,
+    mut actions: ResMut<ButtonInput<InputAction>>,
+    settings_menu: Single<&InheritedVisibility, With<SettingsMenu>>,
+  ) {
+    if !settings_menu.get()
+      || !actions.any_just_pressed([InputAction::Back, InputAction::Pause])
+    {
+      return;
+    }
+    actions.clear_just_pressed(InputAction::Back);
+    actions.clear_just_pressed(InputAction::Pause);
+    commands.run_system_cached(hide_settings_menu);
+  }
+
+  fn hide_settings_menu(
+    mut screen: ResMut<SettingsScreen>,
+    mut settings_menu: Single<&mut Visibility, With<SettingsMenu>>,
+    mut menus: Query<&mut Visibility, (With<Menu>, Without<SettingsMenu>)>,
+  ) {
+    **settings_menu = Visibility::Hidden;
+    screen.rebinding = None;
+    for entity in screen.covered_menus.drain(..) {
+      if let Ok(mut visibility) = menus.get_mut(entity) {
+        *visibility = Visibility::Inherited;
+      }
+    }
+  }
+
+  fn capture_binding(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut actions: ResMut<ButtonInput<InputAction>>,
+    mut screen: ResMut<SettingsScreen>,
+    mut settings: ResMut<Settings>,
+  ) {
+    let Some(action) = screen.rebinding else {
+      return;
+    };
+    // nothing else reacts to inputs while a key is being picked
+    actions.clear();
+    let Some(&key) = keys.get_just_pressed().next() else {
+      return;
+    };
+    let bound_to = |action| {
+      settings
+        .bindings
+        .get(action)
+        .contains(&InputBinding::Key(key))
+    };
+    // the back key cancels, and the pause key can't flap as well
+    if bound_to(InputAction::Back) {
+      screen.rebinding = None;
+      return;
+    }
+    if action != InputAction::Pause && bound_to(InputAction::Pause) {
+      return;
+    }
+    let mut bindings = settings.bindings.get(action).to_vec();
+    bindings.retain(|binding| !matches!(binding, InputBinding::Key(_)));
+    bindings.insert(0, InputBinding::Key(key));
+    settings.bindings.rebind(action, bindings);
+    screen.rebinding = None;
+  }
+
+  fn adjust_focused_setting(
+    actions: Res<ButtonInput<InputAction>>,
+    mut settings: ResMut<Settings>,
+    settings_menu: Single<
+      (Entity, &Menu, &InheritedVisibility),
+      With<SettingsMenu>,
+    >,
+    entries: Query<(&MenuEntry, &SettingsEntry)>,
+  ) {
+    let (menu_entity, menu, visibility) = settings_menu.into_inner();
+    let delta = i32::from(actions.just_pressed(InputAction::Right))
+      - i32::from(actions.just_pressed(InputAction::Left));
+    if !visibility.get() || delta == 0 {
+      return;
+    }
+    let focused = entries.iter().find(|(entry, _)| {
+      entry.menu == menu_entity && entry.index == menu.focus
+    });
+    if let Some((_, entry)) = focused {
+      entry.step(&mut settings, delta, false);
+    }
+  }
+
+  fn activate_setting(
+    mut commands: Commands,
+    mut events: EventReader<MenuActivated>,
+    entries: Query<&SettingsEntry>,
+    mut screen: ResMut<SettingsScreen>,
+    mut settings: ResMut<Settings>,
+  ) {
+    for entry in events.read().filter_map(|e| entries.get(e.0).ok()) {
+      match entry {
+        SettingsEntry::Binding(action) => screen.rebinding = Some(*action),
+        SettingsEntry::Back => commands.run_system_cached(hide_settings_menu),
+        entry => entry.step(&mut settings, 1, true),
+      }
+    }
+  }
+
+  fn update_setting_labels(
+    settings: Res<Settings>,
+    screen: Res<SettingsScreen>,
+    mut entries: Query<(&SettingsEntry, &mut Text)>,
+  ) {
+    if !settings.is_changed() && !screen.is_changed() {
+      return;
+    }
+    for (entry, mut text) in &mut entries {
+      let label = entry.label(&settings, &screen);
+      if text.0 != label {
+        text.0 = label;
+      }
+    }
+  }
+}
@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+
+use crate::{
+  bird::{
+    hit_obstacles,
+    respawn_bird,
+    Bird,
+    BirdCount,
+    BirdDied,
+    Dead,
+    Player,
+  },
+  physics::{Position, PreviousPosition},
+  state::{GameState, OnRunStart},
+};
+
+/// Lets two players race their birds on the same course.
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_systems(
+        OnEnter(GameState::Idle),
+        assign_players
+          .after(respawn_bird)
+          .run_if(resource_exists::<Versus>),
+      )
+      .add_systems(
+        OnRunStart,
+        (reset_winner, assign_players.after(respawn_bird))
+          .run_if(resource_exists::<Versus>),
+      )
+      .add_systems(
+        FixedUpdate,
+        decide_winner
+          .after(hit_obstacles)
+          .run_if(in_state(GameState::Going).and(resource_exists::<Versus>)),
+      )
+      .add_systems(
+        OnEnter(GameState::MainMenu),
+        finish_versus.run_if(resource_exists::<Versus>),
+      );
+  }
+}
+
+/// Exists while two players play against each other. Versus runs don't make
+/// it to the leaderboard and aren't saved as replays.
+#[derive(Resource, Debug)]
+pub struct Versus {
+  /// Player whose bird outlived the other one in the last run, if one did.
+  pub winner: Option<Player>,
+  /// Bird count to go back to once the players are done.
+  previous_bird_count: BirdCount,
+}
+
+impl Versus {
+  /// How far apart vertically the birds start, so that both can be seen.
+  const SPAWN_SPREAD: f32 = 32.0;
+}
+
+/// Starts versus mode, beginning with a run waiting for the players.
+pub(crate) fn start_versus(
+  mut commands: Commands,
+  mut bird_count: ResMut<BirdCount>,
+  mut next_state: ResMut<NextState<GameState>>,
+) {
+  commands.insert_resource(Versus {
+    winner: None,
+    previous_bird_count: *bird_count,
+  });
+  *bird_count = BirdCount(Player::ALL.len());
+  next_state.set(GameState::Idle);
+}
+
+/// Gives each bird to a player, telling them apart by color and height.
+fn assign_players(
+  mut commands: Commands,
+  mut birds: Query<
+    (
+      Entity,
+      &mut Position,
+      &mut PreviousPosition,
+      &mut Transform,
+      Option<&mut Sprite>,
+    ),
+    With<Bird>,
+  >,
+) {
+  let spread = Versus::SPAWN_SPREAD;
+  for (
+    (bird, mut position, mut previous_position, mut transform, sprite),
+    (index, player),
+  ) in birds.iter_mut().zip(Player::ALL.into_iter().enumerate())
+  {
+    let offset = spread / 2.0 - spread * index as f32;
+    position.y += offset;
+    previous_position.y += offset;
+    transform.translation.y += offset;
+    if let Some(mut sprite) = sprite {
+      sprite.color = player.tint();
+    }
+    commands.entity(bird).insert(player);
+  }
+}
+
+fn reset_winner(mut versus: ResMut<Versus>) {
+  versus.winner = None;
+}
+
+/// Crowns the player whose bird died last. Birds dying on the same tick
+/// leave the run without a winner.
+fn decide_winner(
+  mut versus: ResMut<Versus>,
+  mut events: EventReader<BirdDied>,
+  alive_birds: Query<(), (With<Bird>, Without<Dead>)>,
+  players: Query<&Player>,
+) {
+  let last_dead: Vec<_> = events.read().map(|died| died.bird).collect();
+  if last_dead.is_empty() || !alive_birds.is_empty() {
+    return;
+  }
+  versus.winner = match last_dead[..] {
+    [bird] => players.get(bird).ok().copied(),
+    _ => None,
+  };
+}
+
+fn finish_versus(
+  mut commands: Commands,
+  versus: Res<Versus>,
+  mut bird_count: ResMut<BirdCount>,
+) {
+  *bird_count = versus.previous_bird_count;
+  commands.remove_resource::<Versus>();
+}
@@ -0,0 +1,59 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use floppy_burp::{rng::RunSeed, state::GameState, HeadlessPlugin};
+
+/// A headless game laid out by `seed`, stepping 50 ms of time per update.
+/// Resources inserted before the first update are there when it starts.
+pub fn app(seed: u64) -> App {
+  let mut app = App::new();
+  app
+    .insert_resource(RunSeed::Fixed(seed))
+    .add_plugins(HeadlessPlugin)
+    .insert_resource(TimeUpdateStrategy::ManualDuration(
+      Duration::from_millis(50),
+    ));
+  app
+}
+
+pub fn state(app: &App) -> GameState {
+  *app.world().resource::<State<GameState>>().get()
+}
+
+/// Holds `key` down for one update.
+pub fn tap(app: &mut App, key: KeyCode) {
+  press(app, key);
+  app.update();
+  release(app, key);
+}
+
+pub fn press(app: &mut App, key: KeyCode) {
+  app
+    .world_mut()
+    .resource_mut::<ButtonInput<KeyCode>>()
+    .press(key);
+}
+
+pub fn release(app: &mut App, key: KeyCode) {
+  app
+    .world_mut()
+    .resource_mut::<ButtonInput<KeyCode>>()
+    .release(key);
+}
+
+/// Starts a run from [`GameState::Idle`] the way a player does.
+pub fn start_run(app: &mut App) {
+  // the first update only starts the clock
+  app.update();
+  assert_eq!(state(app), GameState::Idle);
+  // the key is still down once the run starts, so the bird flaps
+  press(app, KeyCode::Space);
+  app.update();
+  app.update();
+  release(app, KeyCode::Space);
+  assert_eq!(state(app), GameState::Going);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{start_run, state};
+use floppy_burp::{
+  bird::{Bird, BirdDied, Flapped, HitObstacle},
+  state::{GameState, RunStarted},
+};
+
+/// Gameplay events sent over a run, in order.
+#[derive(Resource, Default)]
+struct Seen {
+  runs_started: Vec<RunStarted>,
+  flaps: Vec<Flapped>,
+  hits: Vec<HitObstacle>,
+  deaths: Vec<BirdDied>,
+}
+
+fn collect(
+  mut seen: ResMut<Seen>,
+  mut run_started: EventReader<RunStarted>,
+  mut flapped: EventReader<Flapped>,
+  mut hit_obstacle: EventReader<HitObstacle>,
+  mut bird_died: EventReader<BirdDied>,
+) {
+  seen.runs_started.extend(run_started.read());
+  seen.flaps.extend(flapped.read());
+  seen.hits.extend(hit_obstacle.read());
+  seen.deaths.extend(bird_died.read());
+}
+
+#[test]
+fn run_sends_gameplay_events() {
+  let mut app = common::app(42);
+  app.init_resource::<Seen>().add_systems(Last, collect);
+  start_run(&mut app);
+  for _ in 0..200 {
+    if state(&app) == GameState::GameOver {
+      break;
+    }
+    app.update();
+  }
+  let bird = app
+    .world_mut()
+    .query_filtered::<Entity, With<Bird>>()
+    .single(app.world());
+  let seen = app.world().resource::<Seen>();
+  assert_eq!(seen.runs_started.len(), 1);
+  assert_eq!(seen.runs_started[0].seed, 42);
+  assert!(!seen.flaps.is_empty());
+  assert!(seen.flaps.iter().all(|flapped| flapped.bird == bird));
+  assert_eq!(seen.hits.len(), 1);
+  assert_eq!(seen.hits[0].bird, bird);
+  assert_eq!(seen.deaths.len(), 1);
+  assert_eq!(seen.deaths[0].bird, bird);
+  assert_eq!(seen.deaths[0].cause, seen.hits[0].kind);
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use common::{press, release, start_run, state, tap};
+use floppy_burp::{
+  bird::{Bird, Dead, Velocity},
+  config::GameConfig,
+  state::GameState,
+};
+
+/// A run the test flaps for with the space key, one fixed tick per update.
+struct Run {
+  app: App,
+  flaps: usize,
+  velocity: f32,
+}
+
+impl Run {
+  fn new(config: GameConfig) -> Self {
+    let mut app = common::app(7);
+    app.insert_resource(config);
+    start_run(&mut app);
+    let timestep = app.world().resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+    let mut run = Self {
+      app,
+      flaps: 0,
+      velocity: 0.0,
+    };
+    // lets the cooldown of any flap that started the run go by
+    run.steps(false, 16);
+    run.flaps = 0;
+    run
+  }
+
+  /// Runs a tick with the flap key held down or not.
+  fn step(&mut self, flap: bool) {
+    if flap {
+      press(&mut self.app, KeyCode::Space);
+    } else {
+      release(&mut self.app, KeyCode::Space);
+    }
+    self.app.update();
+    let world = self.app.world_mut();
+    let (velocity, dead) = world
+      .query_filtered::<(&Velocity, Has<Dead>), With<Bird>>()
+      .single(world);
+    assert!(!dead, "the bird died");
+    // nothing but a flap makes the bird go up faster
+    if **velocity > self.velocity {
+      self.flaps += 1;
+    }
+    self.velocity = **velocity;
+  }
+
+  fn steps(&mut self, flap: bool, ticks: usize) {
+    for _ in 0..ticks {
+      self.step(flap);
+    }
+  }
+}
+
+/// Tuning whose times are counted in fixed ticks of 1/64 s.
+fn config(cooldown_ticks: f32, buffer_ticks: f32) -> GameConfig {
+  let mut config = GameConfig::default();
+  config.bird.flap_cooldown_secs = cooldown_ticks / 64.0;
+  config.bird.flap_buffer_secs = buffer_ticks / 64.0;
+  config
+}
+
+#[test]
+fn flaps_only_on_new_press() {
+  let mut run = Run::new(config(4.0, 0.0));
+  run.step(true);
+  assert_eq!(run.flaps, 1);
+  // holding past the cooldown doesn't flap again
+  run.steps(true, 10);
+  assert_eq!(run.flaps, 1);
+  run.step(false);
+  run.step(true);
+  assert_eq!(run.flaps, 2);
+}
+
+#[test]
+fn cooldown_blocks_second_flap() {
+  let mut run = Run::new(config(8.0, 0.0));
+  run.step(true);
+  run.step(false);
+  run.step(true);
+  run.steps(false, 10);
+  assert_eq!(run.flaps, 1);
+}
+
+#[test]
+fn press_during_cooldown_flaps_once_it_is_over() {
+  let mut run = Run::new(config(8.0, 8.0));
+  run.step(true);
+  run.step(false);
+  run.step(true);
+  run.steps(false, 4);
+  assert_eq!(run.flaps, 1);
+  run.steps(false, 4);
+  assert_eq!(run.flaps, 2);
+}
+
+#[test]
+fn buffered_press_expires() {
+  let mut run = Run::new(config(16.0, 4.0));
+  run.step(true);
+  run.step(false);
+  run.step(true);
+  run.steps(false, 20);
+  assert_eq!(run.flaps, 1);
+}
+
+#[test]
+fn holding_flaps_higher_only_when_enabled() {
+  let mut config = config(4.0, 0.0);
+  config.bird.max_hold_secs = 8.0 / 64.0;
+  let mut run = Run::new(config.clone());
+  run.step(true);
+  let flap_velocity = run.velocity;
+  run.steps(true, 4);
+  assert!(run.velocity < flap_velocity);
+
+  config.bird.hold_to_flap_higher = true;
+  let mut run = Run::new(config);
+  run.step(true);
+  let flap_velocity = run.velocity;
+  run.steps(true, 4);
+  assert_eq!(run.velocity, flap_velocity);
+  // holding for longer than allowed lets the bird fall again
+  run.steps(true, 8);
+  assert!(run.velocity < flap_velocity);
+  // as does letting go
+  run.step(false);
+  run.step(true);
+  run.step(false);
+  assert!(run.velocity < flap_velocity);
+}
+
+#[test]
+fn keys_pressed_while_paused_do_not_flap() {
+  let mut run = Run::new(config(4.0, 0.0));
+  tap(&mut run.app, KeyCode::Escape);
+  run.step(false);
+  assert_eq!(state(&run.app), GameState::Paused);
+  run.step(true);
+  run.step(false);
+  // nor does holding the key while the run resumes
+  run.step(true);
+  press(&mut run.app, KeyCode::Escape);
+  run.step(true);
+  release(&mut run.app, KeyCode::Escape);
+  run.steps(true, 4);
+  assert_eq!(state(&run.app), GameState::Going);
+  assert_eq!(run.flaps, 0);
+  run.step(false);
+  run.step(true);
+  assert_eq!(run.flaps, 1);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{start_run, state};
+use floppy_burp::{
+  bird::{Bird, BirdController, BirdControllers, BirdCount, Dead},
+  state::GameState,
+};
+
+#[test]
+fn birds_fly_until_the_last_one_dies() {
+  let mut app = common::app(3);
+  app
+    .insert_resource(BirdCount(3))
+    // the third bird gets the default controller, and nobody plays it
+    .insert_resource(BirdControllers(vec![
+      BirdController::Autopilot,
+      BirdController::External,
+    ]));
+  start_run(&mut app);
+
+  let mut controllers = app
+    .world_mut()
+    .query_filtered::<&BirdController, With<Bird>>();
+  let controllers: Vec<_> = controllers.iter(app.world()).copied().collect();
+  assert_eq!(controllers.len(), 3);
+  for controller in [
+    BirdController::Autopilot,
+    BirdController::External,
+    BirdController::Player,
+  ] {
+    assert!(controllers.contains(&controller), "no {controller:?} bird");
+  }
+
+  for _ in 0..200 {
+    app.update();
+  }
+  // only the autopilot keeps its bird alive
+  let mut alive = app
+    .world_mut()
+    .query_filtered::<&BirdController, (With<Bird>, Without<Dead>)>();
+  let alive: Vec<_> = alive.iter(app.world()).copied().collect();
+  assert_eq!(alive, [BirdController::Autopilot]);
+  assert_eq!(state(&app), GameState::Going);
+
+  let mut controllers = app.world_mut().query::<&mut BirdController>();
+  for mut controller in controllers.iter_mut(app.world_mut()) {
+    *controller = BirdController::External;
+  }
+  for _ in 0..200 {
+    if state(&app) == GameState::GameOver {
+      break;
+    }
+    app.update();
+  }
+  assert_eq!(state(&app), GameState::GameOver);
+}
@@ -0,0 +1,60 @@
+use floppy_burp::{
+  bird::BirdCount,
+  gym::{Action, Env, Step},
+};
+
+/// Flaps whenever the bird is below the gap ahead, or the middle of the
+/// screen if there is none.
+fn play(env: &mut Env, seed: u64, max_steps: usize) -> Vec<Step> {
+  let mut observation = env.reset(seed);
+  let mut action = Action::Wait;
+  let mut steps = Vec::new();
+  for _ in 0..max_steps {
+    let low = observation
+      .next_gap
+      .map_or(0.0, |gap| gap.center - gap.height / 2.0 + 20.0);
+    action = if action == Action::Wait && observation.bird_y < low {
+      Action::Flap
+    } else {
+      Action::Wait
+    };
+    let step = env.step(action);
+    steps.push(step);
+    if step.done {
+      break;
+    }
+    observation = step.observation;
+  }
+  steps
+}
+
+#[test]
+fn falling_bird_is_done() {
+  let mut env = Env::new();
+  env.reset(7);
+  for _ in 0..1000 {
+    let step = env.step(Action::Wait);
+    if step.done {
+      assert!(step.reward < 0.0);
+      return;
+    }
+  }
+  panic!("the bird didn't fall to the ground");
+}
+
+#[test]
+fn same_seed_plays_same_run() {
+  let mut env = Env::new();
+  let run = play(&mut env, 3, 2000);
+  assert_eq!(run, play(&mut env, 3, 2000));
+  assert_eq!(run, play(&mut Env::new(), 3, 2000));
+  assert_ne!(run, play(&mut env, 4, 2000));
+}
+
+#[test]
+#[should_panic(expected = "single bird")]
+fn several_birds_are_rejected() {
+  let mut env = Env::new();
+  env.app().insert_resource(BirdCount(2));
+  env.reset(7);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use bevy::prelude::*;
+use common::{press, release, start_run, state};
+use floppy_burp::{
+  bird::Bird,
+  pipe::{Pipe, PipeGap},
+  state::GameState,
+};
+
+/// Taps the flap key while the bird is below the gap ahead.
+fn fly_toward_gap(app: &mut App) {
+  let world = app.world_mut();
+  let bird = world
+    .query_filtered::<&Transform, With<Bird>>()
+    .single(world)
+    .translation;
+  let mut pipes = world.query_filtered::<(&Transform, &PipeGap), With<Pipe>>();
+  let gap = pipes
+    .iter(world)
+    .filter(|(pipe, _)| pipe.translation.x + Pipe::HITBOX_SIZE.x > bird.x)
+    .min_by(|(a, _), (b, _)| a.translation.x.total_cmp(&b.translation.x))
+    .map_or(0.0, |(_, gap)| gap.center);
+  let keys = world.resource::<ButtonInput<KeyCode>>();
+  // the key is let go in between flaps, as only new presses flap
+  if bird.y < gap - 10.0 && !keys.pressed(KeyCode::Space) {
+    press(app, KeyCode::Space);
+  } else {
+    release(app, KeyCode::Space);
+  }
+}
+
+/// Centers of the gaps between the pipes a run has spawned, in spawn order.
+fn pipe_layout(seed: u64) -> Vec<f32> {
+  let mut app = common::app(seed);
+  start_run(&mut app);
+  let mut layout = Vec::new();
+  for _ in 0..400 {
+    let mut pipes = app
+      .world_mut()
+      .query_filtered::<(Entity, &PipeGap), With<Pipe>>();
+    for (pipe, gap) in pipes.iter(app.world()) {
+      if !layout.iter().any(|&(seen, _)| seen == pipe) {
+        layout.push((pipe, gap.center));
+      }
+    }
+    if state(&app) != GameState::Going {
+      break;
+    }
+    fly_toward_gap(&mut app);
+    app.update();
+  }
+  layout.into_iter().map(|(_, center)| center).collect()
+}
+
+#[test]
+fn same_seed_lays_out_same_pipes() {
+  let layout = pipe_layout(7);
+  assert!(layout.len() >= 4, "only {} pipes spawned", layout.len());
+  assert_eq!(layout, pipe_layout(7));
+  assert_ne!(layout, pipe_layout(8));
+}
+
+#[test]
+fn bird_left_alone_ends_game() {
+  let mut app = common::app(7);
+  start_run(&mut app);
+  for _ in 0..200 {
+    if state(&app) == GameState::GameOver {
+      break;
+    }
+    app.update();
+  }
+  assert_eq!(state(&app), GameState::GameOver);
+}